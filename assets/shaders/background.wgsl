@@ -1,41 +1,21 @@
-// Import the standard 2d mesh uniforms and set their bind groups
-#import bevy_sprite::mesh2d_view_bind_group
-[[group(0), binding(0)]]
-var<uniform> view: View;
-#import bevy_sprite::mesh2d_struct
-[[group(1), binding(0)]]
-var<uniform> mesh: Mesh2d;
-
-// The structure of the vertex buffer is as specified in `specialize()`
-struct Vertex {
-    [[location(0)]] position: vec3<f32>;
-    [[location(1)]] color: u32;
-};
-
-struct Time {
-    time_since_startup: f32;
+struct BackgroundMaterial {
+    time: f32,
+    // how many bricks were broken in a row
+    combo: f32,
+    // 1.0 right after a hit, fading to 0.0
+    flash: f32,
+    // in world space
+    ball: vec2<f32>,
+    // of the theme, blended over the pattern by its alpha
+    tint: vec4<f32>,
 };
-[[group(2), binding(0)]]
-var<uniform> time: Time;
+@group(1) @binding(0)
+var<uniform> material: BackgroundMaterial;
 
-struct VertexOutput {
-    // The vertex shader must set the on-screen position of the vertex
-    [[builtin(position)]] clip_position: vec4<f32>;
-    // We pass the vertex color to the framgent shader in location 0
-    [[location(0)]] color: vec4<f32>;
+struct FragmentInput {
+    #import bevy_sprite::mesh2d_vertex_output
 };
 
-[[stage(vertex)]]
-fn vertex(vertex: Vertex) -> VertexOutput {
-    var out: VertexOutput;
-    // Project the world position of the mesh into screen position
-    // out.clip_position = view.view_proj * mesh.model * vec4<f32>(vertex.position, 1.0);
-    out.clip_position = vec4<f32>(vertex.position, 1.0);
-    // Unpack the `u32` from the vertex buffer into the `vec4<f32>` used by the fragment shader
-    out.color = vec4<f32>((vec4<u32>(vertex.color) >> vec4<u32>(0u, 8u, 16u, 24u)) & vec4<u32>(255u)) / 255.0;
-    return out;
-}
-
 fn oklab_to_linear_srgb(c: vec3<f32>) -> vec3<f32> {
     let L = c.x;
     let a = c.y;
@@ -54,29 +34,86 @@ fn oklab_to_linear_srgb(c: vec3<f32>) -> vec3<f32> {
     );
 }
 
-struct FragmentInput {
-    // The color is interpolated between vertices by default
-    [[location(0)]] color: vec4<f32>;
-};
+// rotate the hue of an oklab color
+fn hue_shift(c: vec3<f32>, angle: f32) -> vec3<f32> {
+    let cs = cos(angle);
+    let sn = sin(angle);
+    return vec3<f32>(c.x, c.y * cs - c.z * sn, c.y * sn + c.z * cs);
+}
 
-[[stage(fragment)]]
-fn fragment(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+}
+
+// all patterns return a color in oklab
+fn gradient(uv: vec2<f32>) -> vec3<f32> {
     let speed_1 = 0.3;
     let speed_2 = 0.2;
-    let t_1 = sin(time.time_since_startup * speed_1) * 0.5 + 0.5;
-    let t_2 = cos(time.time_since_startup * speed_2);
+    let t_1 = sin(material.time * speed_1) * 0.5 + 0.5;
+    let t_2 = cos(material.time * speed_2);
 
-    let pos = vec2<f32>(in.clip_position[0], in.clip_position[1]);
-    let distance_to_center = distance(pos, vec2<f32>(t_2, t_1)) * 1.2;
+    let pos = uv * 2.0 - vec2<f32>(1.0);
+    let distance_to_center = distance(pos, vec2<f32>(t_2, t_1)) * 0.6;
 
     // blending is done in a perceptual color space: https://bottosson.github.io/posts/oklab/
     let red = vec3<f32>(0.627955, 0.224863, 0.125846);
     let green = vec3<f32>(0.86644, -0.233887, 0.179498);
     let blue = vec3<f32>(0.701674, 0.274566, -0.169156);
     let white = vec3<f32>(1.0, 0.0, 0.0);
-    let mixed = mix(mix(red, blue, t_1), mix(green, white, t_2), distance_to_center);
+    return mix(mix(red, blue, t_1), mix(green, white, t_2), distance_to_center);
+}
+
+// three layers of twinkling stars drifting down at different speeds
+fn starfield(world: vec2<f32>) -> vec3<f32> {
+    var color = vec3<f32>(0.22, -0.01, -0.08);
+    for (var i = 0; i < 3; i = i + 1) {
+        let layer = f32(i);
+        let size = 40.0 + 30.0 * layer;
+        let p = world / size + vec2<f32>(7.1 * layer, material.time * (0.4 - 0.1 * layer));
+        let cell = floor(p);
+        let h = hash(cell + vec2<f32>(layer * 13.0));
+        if (0.9 < h) {
+            let center = vec2<f32>(hash(cell + vec2<f32>(1.0, 0.0)), hash(cell + vec2<f32>(0.0, 1.0)));
+            let d = length(fract(p) - (0.2 + 0.6 * center));
+            let twinkle = 0.6 + 0.4 * sin(material.time * 3.0 + h * 100.0);
+            color.x = color.x + smoothstep(0.08, 0.0, d) * twinkle * (0.8 - 0.2 * layer);
+        }
+    }
+    return color;
+}
+
+fn plasma(uv: vec2<f32>) -> vec3<f32> {
+    let t = material.time * 0.5;
+    let p = uv * 8.0;
+    var v = sin(p.x + t);
+    v = v + sin((p.y + t) / 2.0);
+    v = v + sin((p.x + p.y + t) / 2.0);
+    let c = p + vec2<f32>(sin(t / 3.0), cos(t / 2.0)) * 4.0;
+    v = v + sin(sqrt(dot(c, c) + 1.0) + t);
+    let angle = v * 3.14159265 / 2.0;
+    return vec3<f32>(0.72, 0.14 * cos(angle), 0.14 * sin(angle));
+}
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+#ifdef GRADIENT
+    var mixed = gradient(in.uv);
+#endif
+#ifdef STARFIELD
+    var mixed = starfield(in.world_position.xy);
+#endif
+#ifdef PLASMA
+    var mixed = plasma(in.uv);
+#endif
+
+    // the palette drifts around the hue circle as the combo grows
+    mixed = hue_shift(mixed, 0.35 * max(material.combo - 1.0, 0.0));
+
+    // a glow following the ball, and a pulse on every hit
+    let to_ball = distance(in.world_position.xy, material.ball);
+    let glow = exp(-to_ball / 120.0) * (0.08 + 0.25 * material.flash);
+    mixed.x = mixed.x + glow + 0.12 * material.flash;
 
-    // return vec4<f32>(oklab_to_linear_srgb(mixed), 1.0);
-    return vec4<f32>(mixed, 1.0);
-    // return in.color;
+    let color = mix(oklab_to_linear_srgb(mixed), material.tint.rgb, material.tint.a);
+    return vec4<f32>(color, 1.0);
 }
@@ -0,0 +1,76 @@
+struct PostProcessMaterial {
+    time: f32,
+    // 1.0 right after a hit, fading to 0.0
+    aberration: f32,
+    // of the source texture, in pixels
+    resolution: vec2<f32>,
+    // 1.0 for the full decorative motion, less in reduced-motion mode
+    motion: f32,
+};
+@group(1) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var source_sampler: sampler;
+@group(1) @binding(2)
+var<uniform> material: PostProcessMaterial;
+
+struct FragmentInput {
+    #import bevy_sprite::mesh2d_vertex_output
+};
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+    var uv = in.uv;
+    var inside = 1.0;
+
+#ifdef CRT
+    // bulge the screen like the glass of an old tube
+    let centered = uv * 2.0 - vec2<f32>(1.0);
+    let bent = centered * (vec2<f32>(1.0) + centered.yx * centered.yx * 0.06);
+    uv = bent * 0.5 + vec2<f32>(0.5);
+    // black beyond the rim, masked rather than returned early to keep the sampling uniform
+    let rim = step(vec2<f32>(0.0), uv) * step(uv, vec2<f32>(1.0));
+    inside = rim.x * rim.y;
+#endif
+
+#ifdef CHROMATIC_ABERRATION
+    // the channels drift apart towards the edges, further right after a hit
+    let offset = (uv - vec2<f32>(0.5)) * (0.004 + 0.02 * material.aberration);
+    var color = vec3<f32>(
+        textureSample(source_texture, source_sampler, uv + offset).r,
+        textureSample(source_texture, source_sampler, uv).g,
+        textureSample(source_texture, source_sampler, uv - offset).b,
+    );
+#else
+    var color = textureSample(source_texture, source_sampler, uv).rgb;
+#endif
+
+#ifdef GLOW
+    // add a blur of what is brighter than the threshold, bleeding light around the ball,
+    // the paddle and the flashes
+    let texel = 3.0 / material.resolution;
+    var glow = vec3<f32>(0.0);
+    for (var i = -2; i <= 2; i = i + 1) {
+        for (var j = -2; j <= 2; j = j + 1) {
+            let tap = uv + vec2<f32>(f32(i), f32(j)) * texel;
+            let sampled = textureSample(source_texture, source_sampler, tap).rgb;
+            glow = glow + max(sampled - vec3<f32>(0.6), vec3<f32>(0.0));
+        }
+    }
+    color = color + glow * 0.06;
+#endif
+
+#ifdef CRT
+    // a dark line every other pixel row, slowly rolling, plus a faint flicker calmed down in
+    // reduced-motion mode
+    let line = sin((uv.y * material.resolution.y + material.time * 4.0) * 3.14159265);
+    let flicker = 0.02 * material.motion * sin(material.time * 60.0);
+    color = color * (0.82 + 0.09 * (1.0 + line)) * (1.0 - 0.02 * material.motion + flicker);
+#endif
+
+#ifdef VIGNETTE
+    color = color * smoothstep(0.85, 0.35, distance(uv, vec2<f32>(0.5)));
+#endif
+
+    return vec4<f32>(color * inside, 1.0);
+}
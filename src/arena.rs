@@ -0,0 +1,260 @@
+use bevy::{
+    prelude::*,
+    render::camera::ScalingMode,
+    window::{PrimaryWindow, WindowResized},
+};
+
+/// The geometry of the playing field. Walls, paddle, bricks and the camera are all derived from it.
+#[derive(Clone, Debug, Resource)]
+pub struct Arena {
+    /// distance between the centers of the opposite walls
+    pub bounds: Vec2,
+    pub wall_thickness: f32,
+    /// height of the paddle center above the center of the bottom wall
+    pub paddle_lift: f32,
+    /// gaps between the brick area and the inner sides of the left, right and top walls
+    pub brick_margin: Vec2,
+    pub brick_area_height: f32,
+    /// the world space size visible through the camera, which follows the window size
+    pub viewport: Vec2,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        let bounds = Vec2::new(960.0, 680.0);
+        let wall_thickness = 35.0;
+        Arena {
+            bounds,
+            wall_thickness,
+            paddle_lift: 110.0,
+            brick_margin: Vec2::new(45.0, 55.0),
+            brick_area_height: 180.0,
+            viewport: bounds + Vec2::splat(wall_thickness),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WallSide {
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+#[derive(Component, Debug)]
+pub struct Wall(pub WallSide);
+
+/// The camera looking at the arena, as opposed to the ones compositing the screen
+#[derive(Component, Debug)]
+pub struct ArenaCamera;
+
+impl Arena {
+    /// The smallest world space size that shows the whole arena including the walls.
+    pub fn visible_size(&self) -> Vec2 {
+        self.bounds + Vec2::splat(self.wall_thickness)
+    }
+    /// The area enclosed by the inner faces of the walls.
+    pub fn inner(&self) -> Rect {
+        Rect::from_center_size(Vec2::ZERO, self.bounds - Vec2::splat(self.wall_thickness))
+    }
+    pub fn wall(&self, side: WallSide) -> Rect {
+        let (center, size) = match side {
+            WallSide::Left => (
+                Vec2::new(-self.bounds.x / 2.0, 0.0),
+                Vec2::new(self.wall_thickness, self.bounds.y + self.wall_thickness),
+            ),
+            WallSide::Right => (
+                Vec2::new(self.bounds.x / 2.0, 0.0),
+                Vec2::new(self.wall_thickness, self.bounds.y + self.wall_thickness),
+            ),
+            WallSide::Bottom => (
+                Vec2::new(0.0, -self.bounds.y / 2.0),
+                Vec2::new(self.bounds.x + self.wall_thickness, self.wall_thickness),
+            ),
+            WallSide::Top => (
+                Vec2::new(0.0, self.bounds.y / 2.0),
+                Vec2::new(self.bounds.x + self.wall_thickness, self.wall_thickness),
+            ),
+        };
+        Rect::from_center_size(center, size)
+    }
+    pub fn paddle_y(&self) -> f32 {
+        -self.bounds.y / 2.0 + self.paddle_lift
+    }
+    /// The height of the paddle of the second player in versus, hanging from the top wall.
+    pub fn top_paddle_y(&self) -> f32 {
+        -self.paddle_y()
+    }
+    /// The range of the paddle center x, keeping a paddle of the given width between the walls.
+    pub fn paddle_range(&self, paddle_width: f32) -> (f32, f32) {
+        let half = (self.inner().half_size().x - paddle_width / 2.0).max(0.0);
+        (-half, half)
+    }
+    /// The area bricks are laid out in, hanging from the top wall.
+    pub fn brick_area(&self) -> Rect {
+        let inner = self.inner();
+        let max = inner.max - self.brick_margin;
+        let min = Vec2::new(
+            inner.min.x + self.brick_margin.x,
+            max.y - self.brick_area_height,
+        );
+        Rect::from_corners(min, max)
+    }
+    /// The size of each brick when `rows` x `columns` bricks fill the brick area.
+    pub fn brick_size(&self, rows: usize, columns: usize, spacing: f32) -> Vec2 {
+        let area = self.brick_area().size();
+        let rows = rows.max(1) as f32;
+        let columns = columns.max(1) as f32;
+        Vec2::new(
+            (area.x - spacing * (columns - 1.0)) / columns,
+            (area.y - spacing * (rows - 1.0)) / rows,
+        )
+    }
+    /// The center of the brick at (`row`, `column`), counting rows from the bottom.
+    pub fn brick_position(&self, row: usize, column: usize, size: Vec2, spacing: f32) -> Vec2 {
+        let area = self.brick_area();
+        area.min
+            + size / 2.0
+            + Vec2::new(
+                column as f32 * (size.x + spacing),
+                row as f32 * (size.y + spacing),
+            )
+    }
+    /// The (`row`, `column`) of the cell under `position` in a grid of `rows` x `columns` bricks,
+    /// the inverse of [`Arena::brick_position`].
+    pub fn brick_cell(
+        &self,
+        position: Vec2,
+        rows: usize,
+        columns: usize,
+        spacing: f32,
+    ) -> Option<(usize, usize)> {
+        let pitch = self.brick_size(rows, columns, spacing) + Vec2::splat(spacing);
+        let cell = (position - self.brick_area().min + Vec2::splat(spacing / 2.0)) / pitch;
+        let (row, column) = (cell.y.floor(), cell.x.floor());
+        if row < 0.0 || column < 0.0 || rows as f32 <= row || columns as f32 <= column {
+            return None;
+        }
+        Some((row as usize, column as usize))
+    }
+    pub fn scaling_mode(&self) -> ScalingMode {
+        let size = self.visible_size();
+        ScalingMode::AutoMin {
+            min_width: size.x,
+            min_height: size.y,
+        }
+    }
+    /// The world space size visible in a window of the given logical size.
+    pub fn fitted_viewport(&self, window: Vec2) -> Vec2 {
+        let size = self.visible_size();
+        if window.x <= 0.0 || window.y <= 0.0 {
+            return size;
+        }
+        window * (size.x / window.x).max(size.y / window.y)
+    }
+}
+
+/// Keep the camera projection and [`Arena::viewport`] in sync with the window and the arena.
+pub fn arena_camera_system(
+    mut arena: ResMut<Arena>,
+    mut resized: EventReader<WindowResized>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<&mut OrthographicProjection, With<ArenaCamera>>,
+) {
+    let resized = resized.iter().last().is_some();
+    if !resized && !arena.is_changed() {
+        return;
+    }
+    if let Ok(window) = windows.get_single() {
+        let viewport = arena.fitted_viewport(Vec2::new(window.width(), window.height()));
+        // avoid flagging the arena as changed every frame
+        if arena.viewport != viewport {
+            arena.viewport = viewport;
+        }
+    }
+    for mut projection in cameras.iter_mut() {
+        projection.scaling_mode = arena.scaling_mode();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The default arena and a few odd ones.
+    fn arenas() -> Vec<Arena> {
+        let wide = Arena {
+            bounds: Vec2::new(1400.0, 500.0),
+            wall_thickness: 10.0,
+            ..Default::default()
+        };
+        let thick = Arena {
+            wall_thickness: 80.0,
+            brick_margin: Vec2::ZERO,
+            ..Default::default()
+        };
+        vec![Arena::default(), wide, thick]
+    }
+
+    #[test]
+    fn inner_meets_the_walls() {
+        for arena in arenas() {
+            let inner = arena.inner();
+            assert_eq!(inner.center(), Vec2::ZERO);
+            assert_eq!(inner.min.x, arena.wall(WallSide::Left).max.x);
+            assert_eq!(inner.max.x, arena.wall(WallSide::Right).min.x);
+            assert_eq!(inner.min.y, arena.wall(WallSide::Bottom).max.y);
+            assert_eq!(inner.max.y, arena.wall(WallSide::Top).min.y);
+        }
+    }
+
+    #[test]
+    fn paddle_range_stays_inside_the_walls() {
+        for arena in arenas() {
+            let inner = arena.inner();
+            for width in [0.0, 1.0, 60.0, 120.0, 500.0, 5000.0] {
+                let (min, max) = arena.paddle_range(width);
+                assert_eq!(min, -max, "width {}", width);
+                assert!(min <= max);
+                if width <= inner.width() {
+                    assert!(inner.min.x <= min - width / 2.0 + 1e-3, "width {}", width);
+                    assert!(max + width / 2.0 <= inner.max.x + 1e-3, "width {}", width);
+                } else {
+                    // too wide to move, it stays in the middle
+                    assert_eq!((min, max), (0.0, 0.0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn brick_area_hangs_from_the_top_in_the_middle() {
+        for arena in arenas() {
+            let (inner, area) = (arena.inner(), arena.brick_area());
+            assert_eq!(area.center().x, 0.0);
+            assert_eq!(area.max.y, inner.max.y - arena.brick_margin.y);
+            assert_eq!(area.height(), arena.brick_area_height);
+            assert!(inner.contains(area.min) && inner.contains(area.max));
+            assert!(arena.paddle_y() < area.min.y);
+        }
+    }
+
+    #[test]
+    fn brick_cell_inverts_brick_position() {
+        let arena = Arena::default();
+        let (rows, columns, spacing) = (5, 8, 4.0);
+        let size = arena.brick_size(rows, columns, spacing);
+        for row in 0..rows {
+            for column in 0..columns {
+                let position = arena.brick_position(row, column, size, spacing);
+                assert_eq!(
+                    arena.brick_cell(position, rows, columns, spacing),
+                    Some((row, column))
+                );
+            }
+        }
+        let below = arena.brick_area().min - Vec2::splat(10.0);
+        assert_eq!(arena.brick_cell(below, rows, columns, spacing), None);
+    }
+}
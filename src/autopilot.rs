@@ -0,0 +1,397 @@
+use {
+    crate::{
+        arena::Arena,
+        game::{serve_position, serve_velocity, Ball, Brick, Paddle, TIME_STEP},
+        input::{ActionState, InputAction},
+        state::AppState,
+    },
+    bevy::prelude::*,
+    rand::{rngs::StdRng, Rng, SeedableRng},
+    std::collections::VecDeque,
+};
+
+/// serve positions tried across the paddle range when aiming
+const SERVE_CANDIDATES: usize = 32;
+/// how close to the planned serve the paddle has to be to launch the ball
+const SERVE_TOLERANCE: f32 = 2.0;
+/// the axis held while launching, picking the side of the serve without moving the paddle much
+const SERVE_NUDGE: f32 = 0.01;
+
+/// Fold `x` on a straight line back between the walls at `min_x` and `max_x`.
+fn fold(x: f32, (min_x, max_x): (f32, f32)) -> f32 {
+    let width = (max_x - min_x).max(f32::EPSILON);
+    let t = (x - min_x).rem_euclid(2.0 * width);
+    min_x + if width < t { 2.0 * width - t } else { t }
+}
+
+/// The x where a ball at `position` moving along `velocity` comes down to `landing_y`, bouncing
+/// between the walls at `min_x` and `max_x` and off the ceiling at `top_y`.
+pub fn predict_landing(
+    position: Vec2,
+    velocity: Vec2,
+    landing_y: f32,
+    (min_x, max_x): (f32, f32),
+    top_y: f32,
+) -> f32 {
+    if velocity.y == 0.0 {
+        return position.x;
+    }
+    // the way up and back down, unfolded
+    let rise = if 0.0 < velocity.y {
+        2.0 * (top_y - position.y).max(0.0)
+    } else {
+        0.0
+    };
+    let fall = (position.y - landing_y).max(0.0);
+    let x = position.x + velocity.x * (rise + fall) / velocity.y.abs();
+    fold(x, (min_x, max_x))
+}
+
+/// How many of `bricks` a ball of `ball_size` at `position` rising along `velocity` crosses
+/// before the ceiling at `top_y`, bouncing between the walls at `walls`.
+pub fn bricks_on_the_way_up(
+    position: Vec2,
+    velocity: Vec2,
+    ball_size: f32,
+    bricks: &[Rect],
+    walls: (f32, f32),
+    top_y: f32,
+) -> usize {
+    if velocity.y <= 0.0 {
+        return 0;
+    }
+    let half_ball = ball_size / 2.0;
+    bricks
+        .iter()
+        .filter(|brick| {
+            let y = brick.min.y - half_ball;
+            if y < position.y || top_y < y {
+                return false;
+            }
+            let x = fold(
+                position.x + velocity.x * (y - position.y) / velocity.y,
+                walls,
+            );
+            (x - brick.center().x).abs() <= brick.half_size().x + half_ball
+        })
+        .count()
+}
+
+/// How well the autopilot plays
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Skill {
+    /// seconds between the ball moving and the autopilot seeing it
+    pub reaction: f32,
+    /// the largest error on the predicted landing x, drawn again at each bounce
+    pub noise: f32,
+    /// the share of the paddle range searched for the serve sending the ball through the most
+    /// bricks, from 0.0 serving at once to 1.0 searching all of it
+    pub aim: f32,
+}
+
+impl Skill {
+    pub const NOVICE: Skill = Skill {
+        reaction: 0.3,
+        noise: 60.0,
+        aim: 0.0,
+    };
+    pub const AVERAGE: Skill = Skill {
+        reaction: 0.15,
+        noise: 25.0,
+        aim: 0.3,
+    };
+    pub const EXPERT: Skill = Skill {
+        reaction: 0.05,
+        noise: 5.0,
+        aim: 1.0,
+    };
+    pub const ALL: [(&'static str, Skill); 3] = [
+        ("novice", Skill::NOVICE),
+        ("average", Skill::AVERAGE),
+        ("expert", Skill::EXPERT),
+    ];
+    pub fn named(name: &str) -> Option<Skill> {
+        Skill::ALL
+            .iter()
+            .find(|(skill_name, _)| *skill_name == name)
+            .map(|&(_, skill)| skill)
+    }
+}
+
+/// What the autopilot sees of the game at a moment
+#[derive(Clone, Debug, Default)]
+pub struct Sight {
+    pub ball: Vec2,
+    pub ball_velocity: Vec2,
+    pub ball_size: f32,
+    pub paddle: Vec2,
+    pub paddle_size: Vec2,
+    /// the ball rests on the paddle until served
+    pub serving: bool,
+    /// the breakable bricks left
+    pub bricks: Vec<Rect>,
+}
+
+/// A computer player moving the paddle to where the ball comes down
+#[derive(Debug, Resource)]
+pub struct Autopilot {
+    pub skill: Skill,
+    /// play the game itself, not only behind the title screen
+    pub playing: bool,
+    rng: StdRng,
+    clock: f32,
+    /// the balls seen recently, with their time, waiting out the reaction
+    seen: VecDeque<(f32, Vec2, Vec2)>,
+    /// the error on the landing x since the last bounce
+    error: f32,
+    /// the x to serve from and the axis to serve with, while the ball rests on the paddle
+    serve: Option<(f32, f32)>,
+}
+
+impl Default for Autopilot {
+    fn default() -> Self {
+        Autopilot::new(Skill::EXPERT, 0)
+    }
+}
+
+impl Autopilot {
+    pub fn new(skill: Skill, seed: u64) -> Self {
+        Autopilot {
+            skill,
+            playing: false,
+            rng: StdRng::seed_from_u64(seed),
+            clock: 0.0,
+            seen: VecDeque::new(),
+            error: 0.0,
+            serve: None,
+        }
+    }
+    /// The x the paddle should head for, `delta` seconds after the last look at the game.
+    pub fn steer(&mut self, arena: &Arena, sight: &Sight, delta: f32) -> f32 {
+        self.clock += delta;
+        if sight.serving {
+            // the ball standing still on the paddle tells nothing about the next rally
+            self.seen.clear();
+            let serve = self.plan_serve(arena, sight);
+            self.serve = Some(serve);
+            return serve.0;
+        }
+        self.serve = None;
+        self.seen
+            .push_back((self.clock, sight.ball, sight.ball_velocity));
+        while 1 < self.seen.len() && self.seen[1].0 <= self.clock - self.skill.reaction {
+            self.seen.pop_front();
+        }
+        let (_, ball, velocity) = self.seen[0];
+        // a bounce is a new guess
+        if let Some(&(_, _, previous)) = self.seen.get(1) {
+            if previous.signum() != velocity.signum() {
+                self.error = self.rng.gen_range(-1.0..=1.0) * self.skill.noise;
+            }
+        }
+        let half_ball = sight.ball_size / 2.0;
+        let inner = arena.inner();
+        let walls = (inner.min.x + half_ball, inner.max.x - half_ball);
+        let landing_y = sight.paddle.y + sight.paddle_size.y / 2.0 + half_ball;
+        // the paddle reflects the ball like a wall, so meeting it in the middle is as good as
+        // anywhere else
+        predict_landing(ball, velocity, landing_y, walls, inner.max.y - half_ball) + self.error
+    }
+    /// The axis to hold while launching the ball, once the paddle has carried it to the serve
+    /// planned by the last [`Autopilot::steer`].
+    pub fn launch(&self, sight: &Sight) -> Option<f32> {
+        let (x, axis) = self.serve?;
+        ((sight.paddle.x - x).abs() <= SERVE_TOLERANCE).then_some(axis)
+    }
+    /// The x to serve from and the axis to serve with, sending the ball through the most bricks
+    /// within the reach of the aim, the nearest first. Planned again at every look, as the bricks
+    /// fly in and the paddle moves.
+    fn plan_serve(&self, arena: &Arena, sight: &Sight) -> (f32, f32) {
+        let (min_x, max_x) = arena.paddle_range(sight.paddle_size.x);
+        let reach = self.skill.aim.clamp(0.0, 1.0) * (max_x - min_x);
+        if reach <= 0.0 {
+            return (sight.paddle.x, 0.0);
+        }
+        let half_ball = sight.ball_size / 2.0;
+        let inner = arena.inner();
+        let walls = (inner.min.x + half_ball, inner.max.x - half_ball);
+        let top_y = inner.max.y - half_ball;
+        // a grid fixed over the paddle range, so that the plan holds still as the paddle moves
+        let candidates = (0..=SERVE_CANDIDATES)
+            .map(|i| min_x + (max_x - min_x) * i as f32 / SERVE_CANDIDATES as f32)
+            .filter(|x| (x - sight.paddle.x).abs() <= reach)
+            .chain([sight.paddle.x.clamp(min_x, max_x)]);
+        let mut best = (0, f32::INFINITY, (sight.paddle.x, 0.0));
+        for x in candidates {
+            for axis in [-SERVE_NUDGE, SERVE_NUDGE] {
+                let position = serve_position(Vec2::new(x, sight.paddle.y));
+                let velocity = serve_velocity(axis).truncate();
+                let bricks = bricks_on_the_way_up(
+                    position,
+                    velocity,
+                    sight.ball_size,
+                    &sight.bricks,
+                    walls,
+                    top_y,
+                );
+                let distance = (x - sight.paddle.x).abs();
+                if best.0 < bricks || (best.0 == bricks && distance < best.1) {
+                    best = (bricks, distance, (x, axis));
+                }
+            }
+        }
+        best.2
+    }
+}
+
+/// Move the paddle for the autopilot, behind the title screen or when it plays the game.
+pub fn autopilot_system(
+    state: Res<State<AppState>>,
+    arena: Res<Arena>,
+    mut autopilot: ResMut<Autopilot>,
+    mut actions: ResMut<ActionState>,
+    ball_query: Query<(&Ball, &Transform)>,
+    paddle_query: Query<(&Paddle, &Transform)>,
+    brick_query: Query<(&Brick, &Transform)>,
+) {
+    if state.0 != AppState::Title && !autopilot.playing {
+        return;
+    }
+    let (Ok((ball, ball_transform)), Ok((paddle, paddle_transform))) =
+        (ball_query.get_single(), paddle_query.get_single())
+    else {
+        return;
+    };
+    let sight = Sight {
+        ball: ball_transform.translation.truncate(),
+        ball_velocity: ball.velocity.truncate(),
+        ball_size: ball_transform.scale.x,
+        paddle: paddle_transform.translation.truncate(),
+        paddle_size: paddle_transform.scale.truncate(),
+        serving: ball.serving,
+        bricks: brick_query
+            .iter()
+            .filter(|(brick, _)| !brick.hit && brick.spec.kind.is_breakable())
+            .map(|(_, transform)| {
+                Rect::from_center_size(transform.translation.truncate(), transform.scale.truncate())
+            })
+            .collect(),
+    };
+    let target = autopilot.steer(&arena, &sight, TIME_STEP);
+    // move like a player on the keys would, no faster than the paddle goes
+    actions.pointer_x = None;
+    actions.axis = ((target - sight.paddle.x) / (paddle.speed * TIME_STEP)).clamp(-1.0, 1.0);
+    if let Some(axis) = autopilot.launch(&sight) {
+        actions.axis = axis;
+        actions.press(InputAction::Launch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::game::{BALL_SIZE, PADDLE_SIZE},
+    };
+
+    const WALLS: (f32, f32) = (-100.0, 100.0);
+
+    #[test]
+    fn straight_down_lands_below() {
+        let landing = predict_landing(Vec2::new(10.0, 100.0), Vec2::NEG_Y, -100.0, WALLS, 200.0);
+        assert_eq!(landing, 10.0);
+        let still = predict_landing(Vec2::new(10.0, 100.0), Vec2::X, -100.0, WALLS, 200.0);
+        assert_eq!(still, 10.0);
+    }
+
+    #[test]
+    fn landing_folds_off_both_walls() {
+        let velocity = Vec2::new(1.0, -1.0);
+        // off the right wall only
+        let once = predict_landing(Vec2::ZERO, velocity, -250.0, WALLS, 200.0);
+        assert!((once - -50.0).abs() < 1e-3, "{}", once);
+        // off the right wall, then the left one
+        let twice = predict_landing(Vec2::ZERO, velocity, -450.0, WALLS, 200.0);
+        assert!((twice - 50.0).abs() < 1e-3, "{}", twice);
+        // the other way round
+        let mirrored = predict_landing(Vec2::ZERO, Vec2::new(-1.0, -1.0), -450.0, WALLS, 200.0);
+        assert!((mirrored - -50.0).abs() < 1e-3, "{}", mirrored);
+    }
+
+    /// The bounces off the walls, the ceiling and the paddle, always catching it, before the ball
+    /// served by an autopilot of `skill` reaches one of `bricks`.
+    fn bounces_to_a_brick(skill: Skill, paddle_x: f32, bricks: &[Rect]) -> usize {
+        let arena = Arena::default();
+        let mut autopilot = Autopilot::new(skill, 0);
+        let mut sight = Sight {
+            ball_size: BALL_SIZE,
+            paddle: Vec2::new(paddle_x, arena.paddle_y()),
+            paddle_size: PADDLE_SIZE,
+            serving: true,
+            bricks: bricks.to_vec(),
+            ..Default::default()
+        };
+        let (min_x, max_x) = arena.paddle_range(PADDLE_SIZE.x);
+        let axis = loop {
+            sight.ball = serve_position(sight.paddle);
+            let target = autopilot.steer(&arena, &sight, TIME_STEP);
+            if let Some(axis) = autopilot.launch(&sight) {
+                break axis;
+            }
+            let step = (target - sight.paddle.x).clamp(-500.0 * TIME_STEP, 500.0 * TIME_STEP);
+            sight.paddle.x = (sight.paddle.x + step).clamp(min_x, max_x);
+        };
+        let half_ball = BALL_SIZE / 2.0;
+        let inner = arena.inner();
+        let landing_y = serve_position(sight.paddle).y;
+        let mut position = serve_position(sight.paddle);
+        let mut velocity = serve_velocity(axis).truncate();
+        let mut bounces = 0;
+        while bounces < 100 {
+            position += velocity * TIME_STEP;
+            let ball = Rect::from_center_size(position, Vec2::splat(BALL_SIZE));
+            if bricks.iter().any(|brick| !brick.intersect(ball).is_empty()) {
+                return bounces;
+            }
+            if inner.max.x - half_ball < position.x && 0.0 < velocity.x
+                || position.x < inner.min.x + half_ball && velocity.x < 0.0
+            {
+                velocity.x = -velocity.x;
+                bounces += 1;
+            }
+            if inner.max.y - half_ball < position.y && 0.0 < velocity.y
+                || position.y < landing_y && velocity.y < 0.0
+            {
+                velocity.y = -velocity.y;
+                bounces += 1;
+            }
+        }
+        bounces
+    }
+
+    #[test]
+    fn aiming_reaches_the_bricks_in_fewer_bounces() {
+        // a few bricks up on the left, and the paddle in the middle
+        let bricks: Vec<_> = (0..3)
+            .map(|i| {
+                Rect::from_center_size(
+                    Vec2::new(-320.0, 160.0 + 30.0 * i as f32),
+                    Vec2::new(50.0, 20.0),
+                )
+            })
+            .collect();
+        let novice = bounces_to_a_brick(Skill::NOVICE, 0.0, &bricks);
+        let average = bounces_to_a_brick(Skill::AVERAGE, 0.0, &bricks);
+        let expert = bounces_to_a_brick(Skill::EXPERT, 0.0, &bricks);
+        assert!(0 < novice, "{}", novice);
+        assert!(average <= novice, "{} {}", average, novice);
+        assert!(expert < novice, "{} {}", expert, novice);
+        assert_eq!(expert, 0);
+    }
+
+    #[test]
+    fn rising_ball_comes_back_from_the_ceiling() {
+        let landing = predict_landing(Vec2::ZERO, Vec2::new(1.0, 1.0), -50.0, WALLS, 50.0);
+        assert!((landing - 50.0).abs() < 1e-3, "{}", landing);
+    }
+}
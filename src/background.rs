@@ -1,42 +1,160 @@
-use bevy::{
-    prelude::*,
-    reflect::TypeUuid,
-    render::render_resource::{AsBindGroup, ShaderRef},
-    sprite::{Material2d, MaterialMesh2dBundle},
+use {
+    crate::arena::Arena,
+    bevy::{
+        prelude::*,
+        reflect::TypeUuid,
+        render::{
+            mesh::MeshVertexBufferLayout,
+            render_resource::{
+                AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+            },
+        },
+        sprite::{Material2d, Material2dKey, MaterialMesh2dBundle},
+    },
 };
 
+/// Below every sprite, but still in front of the near plane of the 2D camera
+const BACKGROUND_Z: f32 = 0.0;
+
+/// The quad filling the viewport
+#[derive(Component)]
+pub struct Background;
+
 pub fn setup_background(
-    asset_server: Res<AssetServer>,
+    arena: Res<Arena>,
     mut custom_materials: ResMut<Assets<CustomMaterial>>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     // mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
     let rect = Mesh::from(shape::Quad::default());
-    commands.spawn(MaterialMesh2dBundle {
-        mesh: meshes.add(rect).into(),
-        transform: Transform::from_xyz(0.0, 0.0, 0.0),
-        // material: materials.add(ColorMaterial::from(Color::PURPLE)),
-        material: custom_materials.add(CustomMaterial {
-            texture: asset_server.load("sprites/eye.png"),
-        }),
-        ..default()
-    });
-}
-
-#[derive(AsBindGroup, Debug, Clone, TypeUuid)]
+    commands
+        .spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(rect).into(),
+            transform: Transform {
+                translation: Vec3::new(0.0, 0.0, BACKGROUND_Z),
+                scale: arena.viewport.extend(1.0),
+                ..default()
+            },
+            // material: materials.add(ColorMaterial::from(Color::PURPLE)),
+            material: custom_materials.add(CustomMaterial::default()),
+            ..default()
+        })
+        .insert(Background);
+}
+
+/// Stretch the background over the whole viewport whenever the window is resized.
+pub fn background_layout_system(
+    arena: Res<Arena>,
+    mut query: Query<&mut Transform, With<Background>>,
+) {
+    if !arena.is_changed() {
+        return;
+    }
+    for mut transform in query.iter_mut() {
+        transform.scale = arena.viewport.extend(1.0);
+    }
+}
+
+/// The patterns implemented in `background.wgsl`
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum BackgroundKind {
+    #[default]
+    Gradient,
+    Starfield,
+    Plasma,
+}
+
+impl BackgroundKind {
+    pub const ALL: [BackgroundKind; 3] = [
+        BackgroundKind::Gradient,
+        BackgroundKind::Starfield,
+        BackgroundKind::Plasma,
+    ];
+    /// The background of a level, counting from 1 and cycling through all kinds.
+    pub fn for_level(level: usize) -> Self {
+        BackgroundKind::ALL[level.saturating_sub(1) % BackgroundKind::ALL.len()]
+    }
+    fn shader_def(self) -> &'static str {
+        match self {
+            BackgroundKind::Gradient => "GRADIENT",
+            BackgroundKind::Starfield => "STARFIELD",
+            BackgroundKind::Plasma => "PLASMA",
+        }
+    }
+}
+
+/// The gameplay state the background reacts to, fed by the game every frame
+#[derive(Debug, Default, Resource)]
+pub struct BackgroundFx {
+    pub combo: usize,
+    /// the ball position in world space
+    pub ball: Vec2,
+    /// set to 1.0 on a hit, then fades out
+    pub flash: f32,
+    pub kind: BackgroundKind,
+    /// blended over the pattern by its alpha
+    pub tint: Color,
+}
+
+#[derive(AsBindGroup, Debug, Clone, Default, TypeUuid)]
 #[uuid = "b62bb455-a72c-4b56-87bb-81e0554e234f"]
+#[bind_group_data(BackgroundKind)]
 pub struct CustomMaterial {
-    #[texture(0)]
-    #[sampler(1)]
-    texture: Handle<Image>,
+    /// selects the pattern as a shader def, not as a uniform
+    pub kind: BackgroundKind,
+    // the uniforms sharing a binding are merged into a single struct, see `background.wgsl`
+    #[uniform(0)]
+    pub time: f32,
+    #[uniform(0)]
+    pub combo: f32,
+    #[uniform(0)]
+    pub flash: f32,
+    #[uniform(0)]
+    pub ball: Vec2,
+    #[uniform(0)]
+    pub tint: Color,
 }
 
-impl Material2d for CustomMaterial {
-    fn vertex_shader() -> ShaderRef {
-        "shaders/mesh2d.wgsl".into()
+impl From<&CustomMaterial> for BackgroundKind {
+    fn from(material: &CustomMaterial) -> Self {
+        material.kind
     }
+}
+
+impl Material2d for CustomMaterial {
     fn fragment_shader() -> ShaderRef {
-        "shaders/mesh2d.wgsl".into()
+        "shaders/background.wgsl".into()
+    }
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        key: Material2dKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment
+                .shader_defs
+                .push(key.bind_group_data.shader_def().into());
+        }
+        Ok(())
+    }
+}
+
+/// Copy the state of the game into the background material.
+pub fn background_uniform_system(
+    time: Res<Time>,
+    mut fx: ResMut<BackgroundFx>,
+    mut custom_materials: ResMut<Assets<CustomMaterial>>,
+) {
+    fx.flash *= (-6.0 * time.delta_seconds()).exp();
+    for (_, material) in custom_materials.iter_mut() {
+        material.time = time.elapsed_seconds_wrapped();
+        material.combo = fx.combo as f32;
+        material.flash = fx.flash;
+        material.ball = fx.ball;
+        material.tint = fx.tint;
+        if material.kind != fx.kind {
+            material.kind = fx.kind;
+        }
     }
 }
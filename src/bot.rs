@@ -0,0 +1,328 @@
+use {
+    crate::{
+        game::{Ball, Brick, Paddle, TIME_STEP},
+        hud::Scoreboard,
+        input::{ActionState, InputAction},
+        state::AppState,
+    },
+    bevy::{app::AppExit, prelude::*},
+    serde::{Deserialize, Serialize},
+    std::io::{self, BufRead, Write},
+};
+
+/// A program playing through stdin and stdout, one line each way per tick.
+///
+/// Every tick the game writes an [`Observation`] as a line of JSON and waits for an
+/// [`Action`] line before going on, so that the bot is never left behind however slow it is.
+#[derive(Debug, Default, Resource)]
+pub struct Bot {
+    /// the game is driven by the bot, headless
+    pub connected: bool,
+    /// ticks since the start
+    pub tick: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct BallObservation {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub size: f32,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct BoxObservation {
+    /// the center
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct BrickObservation {
+    #[serde(flatten)]
+    pub rect: BoxObservation,
+    /// `normal`, `steel` or `bonus`
+    pub kind: &'static str,
+    pub hp: usize,
+}
+
+/// What the bot is told about the game at each tick
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Observation {
+    pub tick: u64,
+    /// `title`, `playing`, `level_cleared` or `game_over`, the menus going on by themselves
+    pub state: &'static str,
+    pub score: usize,
+    pub lives: usize,
+    pub level: usize,
+    pub remain_bricks: usize,
+    pub ball: Option<BallObservation>,
+    pub paddle: Option<BoxObservation>,
+    pub bricks: Vec<BrickObservation>,
+}
+
+/// What the bot answers, `{}` or an empty line to leave the paddle where it is
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Action {
+    /// move like on the keys, in `-1.0..=1.0`
+    pub axis: f32,
+    /// move to this x like with the mouse, as fast as the paddle goes
+    pub x: Option<f32>,
+}
+
+impl Action {
+    pub fn parse(line: &str) -> serde_json::Result<Action> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(Action::default());
+        }
+        serde_json::from_str(line)
+    }
+    /// The movement like on the keys for a paddle at `paddle_x` going at `speed`, heading for
+    /// [`Action::x`] no faster than the paddle goes in a tick of `time_step`.
+    pub fn axis(&self, paddle_x: f32, speed: f32, time_step: f32) -> f32 {
+        match self.x {
+            Some(x) => (x - paddle_x) / (speed * time_step),
+            None => self.axis,
+        }
+        .clamp(-1.0, 1.0)
+    }
+}
+
+impl Bot {
+    /// Send `observation` to the bot and wait for its answer, `None` once it has gone.
+    pub fn exchange(&mut self, observation: &Observation) -> Option<Action> {
+        self.exchange_with(
+            observation,
+            &mut io::stdin().lock(),
+            &mut io::stdout().lock(),
+        )
+    }
+    /// [`Bot::exchange`] through `input` and `output` rather than stdin and stdout.
+    pub fn exchange_with(
+        &mut self,
+        observation: &Observation,
+        input: &mut impl BufRead,
+        output: &mut impl Write,
+    ) -> Option<Action> {
+        serde_json::to_writer(&mut *output, observation).ok()?;
+        writeln!(output).ok()?;
+        output.flush().ok()?;
+        self.tick += 1;
+        let mut line = String::new();
+        if input.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        match Action::parse(&line) {
+            Ok(action) => Some(action),
+            Err(e) => {
+                eprintln!("bad action at tick {}: {}", observation.tick, e);
+                Some(Action::default())
+            }
+        }
+    }
+}
+
+/// The name of `state` in an [`Observation`]
+pub fn state_name(state: AppState) -> &'static str {
+    match state {
+        AppState::Title => "title",
+        AppState::Playing => "playing",
+        AppState::Paused => "paused",
+        AppState::LevelCleared => "level_cleared",
+        AppState::GameOver => "game_over",
+        AppState::Editor => "editor",
+    }
+}
+
+/// Tell the bot about the game and move the paddle as it answers, quitting once it has gone.
+#[allow(clippy::too_many_arguments)]
+pub fn bot_system(
+    state: Res<State<AppState>>,
+    scoreboard: Res<Scoreboard>,
+    mut bot: ResMut<Bot>,
+    mut actions: ResMut<ActionState>,
+    ball_query: Query<(&Ball, &Transform)>,
+    paddle_query: Query<(&Paddle, &Transform)>,
+    brick_query: Query<(&Brick, &Transform)>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let rect = |transform: &Transform| BoxObservation {
+        x: transform.translation.x,
+        y: transform.translation.y,
+        width: transform.scale.x,
+        height: transform.scale.y,
+    };
+    let observation = Observation {
+        tick: bot.tick,
+        state: state_name(state.0),
+        score: scoreboard.score,
+        lives: scoreboard.lives,
+        level: scoreboard.level,
+        remain_bricks: scoreboard.remain_bricks,
+        ball: ball_query
+            .get_single()
+            .ok()
+            .map(|(ball, transform)| BallObservation {
+                x: transform.translation.x,
+                y: transform.translation.y,
+                vx: ball.velocity.x,
+                vy: ball.velocity.y,
+                size: transform.scale.x,
+            }),
+        paddle: paddle_query
+            .get_single()
+            .ok()
+            .map(|(_, transform)| rect(transform)),
+        bricks: brick_query
+            .iter()
+            .filter(|(brick, _)| !brick.hit)
+            .map(|(brick, transform)| BrickObservation {
+                rect: rect(transform),
+                kind: brick.spec.kind.name(),
+                hp: brick.spec.hp,
+            })
+            .collect(),
+    };
+    let Some(action) = bot.exchange(&observation) else {
+        exit.send(AppExit);
+        return;
+    };
+    if state.0 != AppState::Playing {
+        return;
+    }
+    let Ok((paddle, transform)) = paddle_query.get_single() else { return; };
+    actions.pointer_x = None;
+    actions.axis = action.axis(transform.translation.x, paddle.speed, TIME_STEP);
+    // the bot doesn't wait to serve
+    actions.press(InputAction::Launch);
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        serde_json::{json, Value},
+        std::io::Cursor,
+    };
+
+    #[test]
+    fn actions_parse_from_json_lines() {
+        let parse = |line| Action::parse(line).unwrap();
+        assert_eq!(parse(""), Action::default());
+        assert_eq!(parse("  \n"), Action::default());
+        assert_eq!(parse("{}"), Action::default());
+        assert_eq!(parse(r#"{"axis": -0.5}"#).axis, -0.5);
+        assert_eq!(parse("{\"x\": 12.5}\n").x, Some(12.5));
+    }
+
+    #[test]
+    fn bad_actions_are_errors() {
+        for line in [
+            "{",
+            "null",
+            "1",
+            "left",
+            r#"{"axis": "left"}"#,
+            r#"{"launch": true}"#,
+            r#"{"axis": 1.0} {}"#,
+        ] {
+            assert!(Action::parse(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn action_axis_heads_for_x_no_faster_than_the_paddle() {
+        let action = |axis, x| Action { axis, x };
+        assert_eq!(action(0.5, None).axis(0.0, 500.0, 0.1), 0.5);
+        assert_eq!(action(3.0, None).axis(0.0, 500.0, 0.1), 1.0);
+        assert_eq!(action(-3.0, None).axis(0.0, 500.0, 0.1), -1.0);
+        // 25 of the 50 the paddle goes in a tick
+        assert_eq!(action(0.0, Some(25.0)).axis(0.0, 500.0, 0.1), 0.5);
+        assert_eq!(action(0.0, Some(-1000.0)).axis(0.0, 500.0, 0.1), -1.0);
+        // the target wins over the axis
+        assert_eq!(action(1.0, Some(0.0)).axis(0.0, 500.0, 0.1), 0.0);
+    }
+
+    #[test]
+    fn exchange_goes_on_past_bad_actions_until_the_bot_leaves() {
+        let mut bot = Bot::default();
+        let mut input = Cursor::new("not json\n{\"axis\": 1}\n{\"axis\": -1}\n");
+        let mut output = Vec::new();
+        let mut actions = Vec::new();
+        while let Some(action) = bot.exchange_with(&Observation::default(), &mut input, &mut output)
+        {
+            actions.push(action);
+        }
+        // a bad line leaves the paddle where it is rather than stopping the game
+        assert_eq!(
+            actions.iter().map(|a| a.axis).collect::<Vec<_>>(),
+            [0.0, 1.0, -1.0]
+        );
+        // one observation a line, the last one unanswered
+        assert_eq!(bot.tick, 4);
+        let lines: Vec<_> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(lines.len(), 4);
+        for line in lines {
+            serde_json::from_str::<Value>(&line).unwrap();
+        }
+    }
+
+    #[test]
+    fn observation_fields_keep_their_names() {
+        let rect = BoxObservation {
+            x: 1.0,
+            y: 2.0,
+            width: 3.0,
+            height: 4.0,
+        };
+        let observation = Observation {
+            tick: 7,
+            state: state_name(AppState::Playing),
+            score: 10,
+            lives: 3,
+            level: 2,
+            remain_bricks: 1,
+            ball: Some(BallObservation {
+                x: 5.0,
+                y: 6.0,
+                vx: 7.0,
+                vy: 8.0,
+                size: 20.0,
+            }),
+            paddle: Some(rect),
+            bricks: vec![BrickObservation {
+                rect,
+                kind: "steel",
+                hp: 2,
+            }],
+        };
+        let box_json = json!({"x": 1.0, "y": 2.0, "width": 3.0, "height": 4.0});
+        assert_eq!(
+            serde_json::to_value(&observation).unwrap(),
+            json!({
+                "tick": 7,
+                "state": "playing",
+                "score": 10,
+                "lives": 3,
+                "level": 2,
+                "remain_bricks": 1,
+                "ball": {"x": 5.0, "y": 6.0, "vx": 7.0, "vy": 8.0, "size": 20.0},
+                "paddle": box_json,
+                // the rect of a brick is flattened into it
+                "bricks": [{"x": 1.0, "y": 2.0, "width": 3.0, "height": 4.0, "kind": "steel", "hp": 2}],
+            })
+        );
+        let empty = serde_json::to_value(Observation::default()).unwrap();
+        assert_eq!(empty["ball"], Value::Null);
+        assert_eq!(empty["paddle"], Value::Null);
+    }
+}
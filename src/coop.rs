@@ -0,0 +1,76 @@
+use {
+    crate::{stats::Stats, versus::Player},
+    bevy::prelude::*,
+};
+
+/// Two players on the same keyboard playing the campaign together, side by side at the bottom.
+///
+/// They share the score, the lives and the combo, which keeps growing when the ball comes down
+/// from the bricks to the other player, while the bricks, shots and misses are tallied for each.
+#[derive(Debug, Default, Resource)]
+pub struct Coop {
+    pub active: bool,
+    /// what each player did over the level in progress
+    pub level: [Stats; 2],
+    /// what each player did over the finished levels
+    pub run: [Stats; 2],
+    /// whether the ball broke a brick since the paddle last sent it up
+    scored_shot: bool,
+}
+
+impl Coop {
+    pub fn start() -> Self {
+        Coop {
+            active: true,
+            ..Default::default()
+        }
+    }
+    pub fn shot(&mut self, player: Player) {
+        self.level[player.index()].shots += 1;
+        self.scored_shot = false;
+    }
+    /// The ball last sent up by `player` broke a brick, worth the `combo` in points.
+    pub fn brick_hit(&mut self, player: Player, combo: usize) {
+        let stats = &mut self.level[player.index()];
+        stats.score += combo;
+        stats.bricks += 1;
+        stats.longest_combo = stats.longest_combo.max(combo);
+        if !self.scored_shot && 0 < stats.shots {
+            stats.hits += 1;
+            self.scored_shot = true;
+        }
+    }
+    pub fn penalty(&mut self, player: Player) {
+        self.level[player.index()].penalties += 1;
+    }
+    /// Close the level, adding it to the run.
+    pub fn finish_level(&mut self) {
+        for (run, level) in self.run.iter_mut().zip(&self.level) {
+            run.add(level);
+        }
+    }
+    pub fn next_level(&mut self) {
+        self.level = Default::default();
+        self.scored_shot = false;
+    }
+    /// The rows of the summary screens for the `stats` of each player.
+    pub fn lines(stats: &[Stats; 2]) -> Vec<String> {
+        [Player::One, Player::Two]
+            .iter()
+            .map(|&player| {
+                let stats = &stats[player.index()];
+                let accuracy = stats
+                    .accuracy()
+                    .map_or("-".to_string(), |a| format!("{:.0}%", 100.0 * a));
+                format!(
+                    "{}  {:>6}  {} bricks  {} misses  {}",
+                    player.name(),
+                    stats.score,
+                    stats.bricks,
+                    stats.penalties,
+                    accuracy,
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,418 @@
+use {
+    crate::{
+        arena::{Arena, ArenaCamera},
+        input::{ActionState, InputAction},
+        level::{BrickKind, Campaign, Level, LevelBrick, LEVEL_FOLDER},
+        menu::NewGameEvent,
+        state::AppState,
+        theme::ActiveTheme,
+    },
+    bevy::{asset::FileAssetIo, prelude::*, window::PrimaryWindow},
+};
+
+/// The empty cells sit right behind the bricks
+const CELL_Z: f32 = 0.9;
+const BRICK_Z: f32 = 1.0;
+/// the bricks of the layouts, shown but not edited, over the grid
+const LAYOUT_Z: f32 = 1.2;
+const HELP_FONT_SIZE: f32 = 18.0;
+const HP_FONT_SIZE: f32 = 20.0;
+const MAX_ROWS: usize = 12;
+const MAX_COLUMNS: usize = 20;
+const MAX_HP: usize = 5;
+/// The colors bricks can be painted with, besides the one the theme gives to their row
+const PALETTE: [Color; 8] = [
+    Color::rgb(0.9, 0.2, 0.2),
+    Color::rgb(0.95, 0.55, 0.1),
+    Color::rgb(0.95, 0.85, 0.2),
+    Color::rgb(0.3, 0.8, 0.3),
+    Color::rgb(0.2, 0.7, 0.8),
+    Color::rgb(0.3, 0.4, 0.95),
+    Color::rgb(0.7, 0.35, 0.9),
+    Color::rgb(0.9, 0.9, 0.9),
+];
+const HELP: &str = "left: place, drag  right: delete  middle: paint  1-3: kind  -/=: hit points  \
+C: color\narrows: grid size  PgUp/PgDn: level  N: new level  Ctrl+S: save  Enter: play-test  \
+Esc: back to the title";
+
+/// The level being edited, and the brush painting it
+#[derive(Debug, Resource)]
+pub struct Editor {
+    pub level: Level,
+    /// the number of the level in the campaign, counting from 1
+    pub number: usize,
+    /// where the level is saved, relative to the asset folder
+    pub path: Option<String>,
+    pub kind: BrickKind,
+    pub hp: usize,
+    /// the color of the bricks painted, the one of the theme if none
+    pub color: Option<Color>,
+    /// whether the game in progress plays the edited level
+    pub play_testing: bool,
+    /// the outcome of the last command
+    status: String,
+    /// the cell of the brick being moved
+    dragging: Option<(usize, usize)>,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Editor {
+            level: Level::default(),
+            number: 1,
+            path: None,
+            kind: BrickKind::Normal,
+            hp: 1,
+            color: None,
+            play_testing: false,
+            status: String::new(),
+            dragging: None,
+        }
+    }
+}
+
+impl Editor {
+    /// Open the level numbered `number` in the campaign, unless its file isn't loaded, so that
+    /// the level and the path it is saved to always come from the same file.
+    fn load(
+        &mut self,
+        number: usize,
+        campaign: &Campaign,
+        levels: &Assets<Level>,
+        asset_server: &AssetServer,
+    ) {
+        let Some(handle) = campaign.handle(number) else {
+            self.status = "no level to load".to_string();
+            return;
+        };
+        let Some(level) = levels.get(handle) else {
+            self.status = format!("level {} isn't loaded", number);
+            return;
+        };
+        self.number = number;
+        self.level = level.clone();
+        self.path = asset_server
+            .get_handle_path(handle)
+            .map(|path| path.path().to_string_lossy().into_owned());
+        self.status = format!("loaded level {}", number);
+    }
+    /// Start an empty level after the last one of the campaign.
+    fn create(&mut self, campaign: &Campaign) {
+        let number = campaign.0.len() + 1;
+        self.number = number;
+        self.level = Level {
+            name: format!("Custom {}", number),
+            bricks: Vec::new(),
+            ..Level::default()
+        };
+        self.path = Some(format!("{}/{:02}-custom.level.ron", LEVEL_FOLDER, number));
+        self.status = "new level".to_string();
+    }
+    fn save(
+        &mut self,
+        campaign: &mut Campaign,
+        levels: &mut Assets<Level>,
+        asset_server: &AssetServer,
+    ) {
+        let Some(path) = self.path.clone() else { return; };
+        let file = FileAssetIo::get_base_path().join("assets").join(&path);
+        if let Err(e) = self.level.save(&file) {
+            self.status = format!("failed to save {}: {}", path, e);
+            return;
+        }
+        // update the loaded level, or add the new one to the campaign
+        let handle: Handle<Level> = asset_server.get_handle(path.as_str());
+        if campaign.0.contains(&handle) {
+            if let Some(level) = levels.get_mut(&handle) {
+                *level = self.level.clone();
+            }
+        } else {
+            campaign.0.push(asset_server.load(path.as_str()));
+        }
+        self.status = format!("saved {}", path);
+    }
+    /// A brick made with the brush.
+    fn brush(&self, row: usize, column: usize) -> LevelBrick {
+        LevelBrick {
+            color: self.color,
+            kind: self.kind,
+            hp: self.hp,
+            ..LevelBrick::new(row, column)
+        }
+    }
+    fn resize(&mut self, rows: usize, columns: usize) {
+        let level = &mut self.level;
+        level.rows = rows.clamp(1, MAX_ROWS);
+        level.columns = columns.clamp(1, MAX_COLUMNS);
+        let (rows, columns) = (level.rows, level.columns);
+        level
+            .bricks
+            .retain(|brick| brick.row < rows && brick.column < columns);
+    }
+}
+
+/// Everything drawn by the editor
+#[derive(Component)]
+pub struct EditorView;
+
+/// Open the first level of the campaign the first time, or come back from a play-test.
+pub fn enter_editor_system(
+    mut editor: ResMut<Editor>,
+    campaign: Res<Campaign>,
+    levels: Res<Assets<Level>>,
+    asset_server: Res<AssetServer>,
+) {
+    if editor.play_testing {
+        editor.play_testing = false;
+        editor.status = "play-test over".to_string();
+    } else if editor.path.is_none() {
+        let number = editor.number;
+        editor.load(number, &campaign, &levels, &asset_server);
+    } else {
+        editor.status.clear();
+    }
+}
+
+pub fn exit_editor_system(mut commands: Commands, views: Query<Entity, With<EditorView>>) {
+    for entity in views.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Change the brush and the grid, switch levels, save and play-test with the keyboard.
+#[allow(clippy::too_many_arguments)]
+pub fn editor_input_system(
+    keyboard: Res<Input<KeyCode>>,
+    actions: Res<ActionState>,
+    mut editor: ResMut<Editor>,
+    mut campaign: ResMut<Campaign>,
+    mut levels: ResMut<Assets<Level>>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut new_game: EventWriter<NewGameEvent>,
+) {
+    if actions.just_pressed(InputAction::Pause) || actions.just_pressed(InputAction::Back) {
+        new_game.send(NewGameEvent::Attract);
+        next_state.set(AppState::Title);
+        return;
+    }
+    if actions.just_pressed(InputAction::Confirm) {
+        if editor.level.breakable_count() == 0 {
+            editor.status = "place a breakable brick first".to_string();
+            return;
+        }
+        editor.play_testing = true;
+        new_game.send(NewGameEvent::Start {
+            level: editor.number,
+        });
+        next_state.set(AppState::Playing);
+        return;
+    }
+    let control = keyboard.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let Some(&key) = keyboard.get_just_pressed().next() else { return; };
+    let editor = &mut *editor;
+    let (rows, columns) = (editor.level.rows, editor.level.columns);
+    match key {
+        KeyCode::Key1 | KeyCode::Key2 | KeyCode::Key3 => {
+            let index = key as usize - KeyCode::Key1 as usize;
+            editor.kind = BrickKind::ALL[index];
+        }
+        KeyCode::Minus => editor.hp = editor.hp.saturating_sub(1).max(1),
+        KeyCode::Equals => editor.hp = (editor.hp + 1).min(MAX_HP),
+        KeyCode::C => {
+            let next = match editor.color {
+                None => 0,
+                Some(color) => PALETTE
+                    .iter()
+                    .position(|&c| c == color)
+                    .map_or(0, |i| i + 1),
+            };
+            editor.color = PALETTE.get(next).copied();
+        }
+        KeyCode::Left => editor.resize(rows, columns.saturating_sub(1)),
+        KeyCode::Right => editor.resize(rows, columns + 1),
+        KeyCode::Down => editor.resize(rows.saturating_sub(1), columns),
+        KeyCode::Up => editor.resize(rows + 1, columns),
+        KeyCode::PageUp => {
+            let number = editor.number.saturating_sub(1).max(1);
+            editor.load(number, &campaign, &levels, &asset_server);
+        }
+        KeyCode::PageDown => {
+            let number = (editor.number + 1).min(campaign.0.len().max(1));
+            editor.load(number, &campaign, &levels, &asset_server);
+        }
+        KeyCode::N => editor.create(&campaign),
+        KeyCode::S if control => editor.save(&mut campaign, &mut levels, &asset_server),
+        _ => (),
+    }
+}
+
+/// Place, move, delete and paint bricks with the mouse, snapping them to the grid.
+pub fn editor_mouse_system(
+    mouse: Res<Input<MouseButton>>,
+    arena: Res<Arena>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<ArenaCamera>>,
+    mut editor: ResMut<Editor>,
+) {
+    if mouse.just_released(MouseButton::Left) && editor.dragging.is_some() {
+        editor.dragging = None;
+    }
+    let Some(cursor) = windows.get_single().ok().and_then(|w| w.cursor_position()) else { return; };
+    let Some(position) = cameras
+        .iter()
+        .find_map(|(camera, transform)| camera.viewport_to_world_2d(transform, cursor))
+    else {
+        return;
+    };
+    let level = &editor.level;
+    let Some((row, column)) = arena.brick_cell(position, level.rows, level.columns, level.spacing)
+    else {
+        return;
+    };
+    let index = level.brick_at(row, column);
+    if mouse.just_pressed(MouseButton::Left) {
+        if index.is_none() {
+            let brick = editor.brush(row, column);
+            editor.level.bricks.push(brick);
+        }
+        editor.dragging = Some((row, column));
+    } else if mouse.pressed(MouseButton::Left) {
+        let Some((from_row, from_column)) = editor.dragging else { return; };
+        if index.is_some() {
+            return;
+        }
+        if let Some(from) = level.brick_at(from_row, from_column) {
+            let brick = &mut editor.level.bricks[from];
+            brick.row = row;
+            brick.column = column;
+        }
+        editor.dragging = Some((row, column));
+    } else if mouse.pressed(MouseButton::Right) {
+        if let Some(index) = index {
+            editor.level.bricks.remove(index);
+        }
+    } else if mouse.pressed(MouseButton::Middle) {
+        let brush = editor.brush(row, column);
+        if let Some(index) = index.filter(|&index| level.bricks[index] != brush) {
+            editor.level.bricks[index] = brush;
+        }
+    }
+}
+
+/// Redraw the grid, the bricks and the status line whenever the level or the brush changes.
+pub fn editor_view_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    editor: Res<Editor>,
+    arena: Res<Arena>,
+    theme: Res<ActiveTheme>,
+    views: Query<Entity, With<EditorView>>,
+) {
+    if !editor.is_changed() && !theme.is_changed() {
+        return;
+    }
+    for entity in views.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let theme = &theme.0;
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let level = &editor.level;
+    let size = arena.brick_size(level.rows, level.columns, level.spacing);
+    for row in 0..level.rows {
+        for column in 0..level.columns {
+            let position = arena.brick_position(row, column, size, level.spacing);
+            let brick = level
+                .brick_at(row, column)
+                .map(|index| &level.bricks[index]);
+            let (color, z) = match brick {
+                Some(brick) => (brick.color(theme, level.rows), BRICK_Z),
+                None => (theme.walls.with_a(0.15), CELL_Z),
+            };
+            commands
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(size),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(position.extend(z)),
+                    ..Default::default()
+                })
+                .insert(EditorView);
+            let Some(brick) = brick.filter(|brick| 1 < brick.hp) else { continue; };
+            commands
+                .spawn(Text2dBundle {
+                    text: Text::from_section(
+                        brick.hp.to_string(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: HP_FONT_SIZE,
+                            color: theme.value,
+                        },
+                    )
+                    .with_alignment(TextAlignment::Center),
+                    transform: Transform::from_translation(position.extend(BRICK_Z + 0.1)),
+                    ..Default::default()
+                })
+                .insert(EditorView);
+        }
+    }
+    let middle = arena.brick_area().center();
+    for layout in &level.layouts {
+        let bands = layout.bands();
+        for (brick, offset) in layout.bricks() {
+            commands
+                .spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: brick.color(theme, bands).with_a(0.6),
+                        custom_size: Some(layout.brick),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation((middle + offset).extend(LAYOUT_Z)),
+                    ..Default::default()
+                })
+                .insert(EditorView);
+        }
+    }
+    let color = match editor.color {
+        None => "theme".to_string(),
+        Some(color) => {
+            let index = PALETTE.iter().position(|&c| c == color).unwrap_or_default();
+            format!("#{}", index + 1)
+        }
+    };
+    let status = format!(
+        "{} {} ({}x{})  brush: {}, {} hp, color {}  {}\n",
+        editor.number,
+        level.name,
+        level.rows,
+        level.columns,
+        editor.kind.name(),
+        editor.hp,
+        color,
+        editor.status,
+    );
+    let style = |color: Color| TextStyle {
+        font: font.clone(),
+        font_size: HELP_FONT_SIZE,
+        color,
+    };
+    commands
+        .spawn(
+            TextBundle::from_sections([
+                TextSection::new(status, style(theme.value)),
+                TextSection::new(HELP, style(theme.label)),
+            ])
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .insert(EditorView);
+}
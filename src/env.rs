@@ -0,0 +1,451 @@
+use {
+    crate::{
+        arena::{Arena, Wall, WallSide},
+        bot::Action,
+        coop::Coop,
+        game::{
+            ball_bundle, ball_collision_system, ball_movement_system, lockstep_time,
+            lockstep_time_system, paddle_bundle, paddle_movement_system, spawn_bricks, wall_bundle,
+            Ball, Brick, GameEvent, GameRng, LevelIntro, Paddle, BALL_SPEED, BALL_START, TIME_STEP,
+        },
+        generator::generate,
+        hud::Scoreboard,
+        input::ActionState,
+        level::{BrickKind, Level},
+        popup::ScorePopupEvent,
+        settings::Settings,
+        stats::Statistics,
+        theme::ActiveTheme,
+        tween::{tween_system, Motion, TweenCompleted},
+        versus::{Player, Versus},
+    },
+    bevy::{ecs::system::CommandQueue, prelude::*, time::TimeSystem},
+};
+
+/// How an [`Env`] shows the game to the agent
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    /// the ball position and velocity, the paddle position, the share of the bricks left and
+    /// the lives, positions from `-1.0` to `1.0` over the arena and velocities in units of
+    /// [`BALL_SPEED`]
+    Features,
+    /// three layers of `columns` by `rows` cells over the arena, row by row from the top, set
+    /// where the bricks, the ball and the paddle cover them, steel bricks at `0.5`
+    Grid { columns: usize, rows: usize },
+}
+
+impl Encoding {
+    /// The length of the observations.
+    pub fn size(self) -> usize {
+        match self {
+            Encoding::Features => 7,
+            Encoding::Grid { columns, rows } => 3 * columns * rows,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EnvConfig {
+    pub encoding: Encoding,
+    /// the reward for a life won, or its opposite for a life lost, on top of the points
+    pub life_reward: f32,
+    /// ticks before an episode is cut short
+    pub max_ticks: usize,
+    /// the level of every episode, instead of a wall generated from the seed
+    pub level: Option<Level>,
+    pub settings: Settings,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            encoding: Encoding::Features,
+            life_reward: 10.0,
+            max_ticks: 60 * 60 * 5,
+            level: None,
+            settings: Settings::default(),
+        }
+    }
+}
+
+/// The game as an environment for reinforcement learning, a level per episode.
+///
+/// It runs the systems of the game without a window or rendering, a tick per
+/// [`step`](Env::step), rewarding the points won and lost on the [`Scoreboard`].
+pub struct Env {
+    app: App,
+    config: EnvConfig,
+    ticks: usize,
+    bricks: usize,
+    score: usize,
+    lives: usize,
+}
+
+impl Env {
+    pub fn new(config: EnvConfig) -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(lockstep_time())
+            .add_system(
+                lockstep_time_system
+                    .in_base_set(CoreSet::First)
+                    .before(TimeSystem),
+            )
+            .insert_resource(Arena::default())
+            .insert_resource(config.settings.clone())
+            .init_resource::<Scoreboard>()
+            .init_resource::<Versus>()
+            .init_resource::<Coop>()
+            .init_resource::<Statistics>()
+            .init_resource::<LevelIntro>()
+            .init_resource::<ActionState>()
+            .init_resource::<ActiveTheme>()
+            .init_resource::<Motion>()
+            .init_resource::<GameRng>()
+            .add_event::<GameEvent>()
+            .add_event::<ScorePopupEvent>()
+            .add_event::<TweenCompleted>()
+            .add_systems(
+                (
+                    paddle_movement_system,
+                    ball_collision_system,
+                    ball_movement_system,
+                    tween_system,
+                )
+                    .chain(),
+            );
+        Env {
+            app,
+            config,
+            ticks: 0,
+            bricks: 0,
+            score: 0,
+            lives: 0,
+        }
+    }
+    /// Start an episode on the wall generated from `seed`, or the level of the configuration,
+    /// once its bricks have landed.
+    pub fn reset(&mut self, seed: u64) -> Vec<f32> {
+        let world = &mut self.app.world;
+        let field: Vec<Entity> = world
+            .query_filtered::<Entity, Or<(With<Ball>, With<Paddle>, With<Brick>, With<Wall>)>>()
+            .iter(world)
+            .collect();
+        for entity in field {
+            world.despawn(entity);
+        }
+        let level = self
+            .config
+            .level
+            .clone()
+            .unwrap_or_else(|| generate(seed, 1));
+        let mut scoreboard = Scoreboard::default();
+        let mut queue = CommandQueue::default();
+        let intro = {
+            let arena = world.resource::<Arena>();
+            let theme = &world.resource::<ActiveTheme>().0;
+            let mut commands = Commands::new(&mut queue, world);
+            for side in [
+                WallSide::Left,
+                WallSide::Right,
+                WallSide::Bottom,
+                WallSide::Top,
+            ] {
+                commands.spawn(wall_bundle(arena, side, theme));
+            }
+            commands.spawn(paddle_bundle(
+                arena,
+                theme,
+                Player::One,
+                WallSide::Bottom,
+                0.0,
+            ));
+            commands.spawn(ball_bundle(theme, BALL_START, false));
+            spawn_bricks(&mut commands, arena, theme, &level, &mut scoreboard)
+        };
+        queue.apply(world);
+        self.bricks = scoreboard.remain_bricks.max(1);
+        self.score = scoreboard.score;
+        self.lives = scoreboard.lives;
+        self.ticks = 0;
+        world.insert_resource(scoreboard);
+        world.insert_resource(Statistics::default());
+        world.insert_resource(ActionState::default());
+        world.insert_resource(LevelIntro(intro));
+        world.insert_resource(GameRng::seeded(seed));
+        // the episode begins with the ball moving
+        while 0.0 < self.app.world.resource::<LevelIntro>().0 {
+            self.app.update();
+        }
+        self.observation()
+    }
+    /// Play a tick with the paddle moved by `action`, returning what follows, the reward and
+    /// whether the episode is over.
+    pub fn step(&mut self, action: Action) -> (Vec<f32>, f32, bool) {
+        let world = &mut self.app.world;
+        let (paddle, transform) = world.query::<(&Paddle, &Transform)>().single(world);
+        let axis = action.axis(transform.translation.x, paddle.speed, TIME_STEP);
+        let mut actions = world.resource_mut::<ActionState>();
+        actions.pointer_x = None;
+        actions.axis = axis;
+        self.app.update();
+        self.ticks += 1;
+        let scoreboard = self.app.world.resource::<Scoreboard>();
+        let reward = scoreboard.score as f32 - self.score as f32
+            + self.config.life_reward * (scoreboard.lives as f32 - self.lives as f32);
+        let done = 0 == scoreboard.remain_bricks
+            || 0 == scoreboard.lives
+            || self.config.max_ticks <= self.ticks;
+        self.score = scoreboard.score;
+        self.lives = scoreboard.lives;
+        (self.observation(), reward, done)
+    }
+    /// The game as it is now, in the encoding of the configuration.
+    pub fn observation(&mut self) -> Vec<f32> {
+        let world = &mut self.app.world;
+        let inner = world.resource::<Arena>().inner();
+        let (ball, ball_transform) = world.query::<(&Ball, &Transform)>().single(world);
+        let ball_rect = rect(ball_transform);
+        let ball_velocity = ball.velocity.truncate();
+        let paddle_rect = rect(
+            world
+                .query_filtered::<&Transform, With<Paddle>>()
+                .single(world),
+        );
+        match self.config.encoding {
+            Encoding::Features => {
+                let scoreboard = world.resource::<Scoreboard>();
+                let position = |p: Vec2| (p - inner.center()) / inner.half_size();
+                let ball = position(ball_rect.center());
+                vec![
+                    ball.x,
+                    ball.y,
+                    ball_velocity.x / BALL_SPEED,
+                    ball_velocity.y / BALL_SPEED,
+                    position(paddle_rect.center()).x,
+                    scoreboard.remain_bricks as f32 / self.bricks as f32,
+                    scoreboard.lives as f32,
+                ]
+            }
+            Encoding::Grid { columns, rows } => {
+                let mut grid = Grid {
+                    cells: vec![0.0; 3 * columns * rows],
+                    columns,
+                    rows,
+                    area: inner,
+                };
+                for (brick, transform) in world.query::<(&Brick, &Transform)>().iter(world) {
+                    if brick.hit {
+                        continue;
+                    }
+                    let value = if brick.spec.kind == BrickKind::Steel {
+                        0.5
+                    } else {
+                        1.0
+                    };
+                    grid.paint(0, rect(transform), value);
+                }
+                grid.paint(1, ball_rect, 1.0);
+                grid.paint(2, paddle_rect, 1.0);
+                grid.cells
+            }
+        }
+    }
+}
+
+fn rect(transform: &Transform) -> Rect {
+    Rect::from_center_size(transform.translation.truncate(), transform.scale.truncate())
+}
+
+/// Layers of cells over an area of the world
+struct Grid {
+    cells: Vec<f32>,
+    columns: usize,
+    rows: usize,
+    area: Rect,
+}
+
+impl Grid {
+    /// Set the cells of `layer` which `rect` covers to `value`, keeping the highest.
+    fn paint(&mut self, layer: usize, rect: Rect, value: f32) {
+        let cell = self.area.size() / Vec2::new(self.columns as f32, self.rows as f32);
+        let rect = rect.intersect(self.area);
+        if rect.is_empty() {
+            return;
+        }
+        let first = ((rect.min - self.area.min) / cell).floor();
+        let last = ((rect.max - self.area.min) / cell).ceil();
+        let columns = first.x as usize..(last.x as usize).min(self.columns);
+        // rows from the top
+        let rows = (self.rows - (last.y as usize).min(self.rows))..(self.rows - first.y as usize);
+        for row in rows {
+            for column in columns.clone() {
+                let index = (layer * self.rows + row) * self.columns + column;
+                self.cells[index] = self.cells[index].max(value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::level::LevelBrick};
+
+    /// An episode on a single brick filling the brick area, taking `hp` hits.
+    fn one_brick_env(hp: usize) -> Env {
+        Env::new(EnvConfig {
+            level: Some(Level {
+                name: "one".to_string(),
+                rows: 1,
+                columns: 1,
+                spacing: 0.0,
+                bricks: vec![LevelBrick {
+                    hp,
+                    ..LevelBrick::new(0, 0)
+                }],
+                layouts: Vec::new(),
+            }),
+            ..Default::default()
+        })
+    }
+
+    /// Follow the ball with the paddle.
+    fn catch(env: &mut Env) -> Action {
+        let world = &mut env.app.world;
+        let ball = world
+            .query_filtered::<&Transform, With<Ball>>()
+            .single(world);
+        Action {
+            axis: 0.0,
+            x: Some(ball.translation.x),
+        }
+    }
+
+    #[test]
+    fn same_seed_and_actions_play_the_same() {
+        let play = |env: &mut Env| {
+            let mut steps = vec![(env.reset(7), 0.0, false)];
+            for tick in 0..600 {
+                // sweeping across rather than following the ball, to hit and to miss
+                let axis = (tick as f32 / 40.0).sin();
+                steps.push(env.step(Action { axis, x: None }));
+            }
+            steps
+        };
+        let mut env = Env::new(EnvConfig::default());
+        let first = play(&mut env);
+        assert_eq!(first, play(&mut Env::new(EnvConfig::default())));
+        // an episode starting over is the same as in a new environment
+        assert_eq!(first, play(&mut env));
+    }
+
+    #[test]
+    fn breaking_a_brick_is_rewarded() {
+        let mut env = Env::new(EnvConfig::default());
+        let mut left = env.reset(1)[5];
+        for _ in 0..60 * 60 {
+            let action = catch(&mut env);
+            let (observation, reward, done) = env.step(action);
+            if observation[5] < left {
+                assert!(0.0 < reward, "{}", reward);
+                return;
+            }
+            left = observation[5];
+            assert!(!done);
+        }
+        panic!("no brick broken in a minute");
+    }
+
+    #[test]
+    fn episode_is_done_once_cleared() {
+        let mut env = one_brick_env(1);
+        assert_eq!(env.reset(0)[5], 1.0);
+        for _ in 0..60 * 60 {
+            let action = catch(&mut env);
+            let (observation, _, done) = env.step(action);
+            if done {
+                assert_eq!(observation[5], 0.0);
+                assert_eq!(observation[6], 3.0);
+                return;
+            }
+        }
+        panic!("the brick still stands after a minute");
+    }
+
+    #[test]
+    fn episode_is_done_once_lost() {
+        // a brick the ball alone could never break in time
+        let mut env = one_brick_env(1000);
+        env.reset(0);
+        let mut lost = 0.0;
+        for _ in 0..60 * 60 {
+            // out of the way in a corner
+            let (observation, reward, done) = env.step(Action {
+                axis: 0.0,
+                x: Some(-1000.0),
+            });
+            lost += reward.min(0.0);
+            if done {
+                assert_eq!(observation[6], 0.0);
+                assert!(lost <= -3.0 * env.config.life_reward, "{}", lost);
+                return;
+            }
+        }
+        panic!("the game goes on after a minute without the paddle");
+    }
+
+    /// Ten by five cells of ten units over three layers.
+    fn grid() -> Grid {
+        Grid {
+            cells: vec![0.0; 3 * 10 * 5],
+            columns: 10,
+            rows: 5,
+            area: Rect::new(0.0, 0.0, 100.0, 50.0),
+        }
+    }
+
+    /// The cells set in `grid`, as their layer, row from the top, column and value.
+    fn painted(grid: &Grid) -> Vec<(usize, usize, usize, f32)> {
+        let per_layer = grid.rows * grid.columns;
+        grid.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &value)| 0.0 < value)
+            .map(|(i, &value)| {
+                let (layer, rest) = (i / per_layer, i % per_layer);
+                (layer, rest / grid.columns, rest % grid.columns, value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn paint_clips_to_the_edges() {
+        let mut grid = grid();
+        // sticking out to the left at the bottom, and to the right at the top
+        grid.paint(0, Rect::new(-20.0, 0.0, 5.0, 10.0), 1.0);
+        grid.paint(1, Rect::new(95.0, 45.0, 130.0, 80.0), 1.0);
+        assert_eq!(painted(&grid), [(0, 4, 0, 1.0), (1, 0, 9, 1.0)]);
+    }
+
+    #[test]
+    fn paint_covers_every_cell_touched() {
+        let mut grid = grid();
+        grid.paint(2, Rect::new(15.0, 5.0, 31.0, 15.0), 1.0);
+        let cells: Vec<_> = painted(&grid).iter().map(|&(_, r, c, _)| (r, c)).collect();
+        assert_eq!(cells, [(3, 1), (3, 2), (3, 3), (4, 1), (4, 2), (4, 3)]);
+    }
+
+    #[test]
+    fn paint_keeps_the_highest_and_skips_the_outside() {
+        let mut grid = grid();
+        grid.paint(0, Rect::new(0.0, 0.0, 100.0, 50.0), 0.5);
+        grid.paint(0, Rect::new(40.0, 20.0, 50.0, 30.0), 1.0);
+        grid.paint(0, Rect::new(40.0, 20.0, 50.0, 30.0), 0.5);
+        grid.paint(1, Rect::new(-30.0, -30.0, -10.0, -10.0), 1.0);
+        grid.paint(1, Rect::new(100.0, 0.0, 120.0, 50.0), 1.0);
+        let cells = painted(&grid);
+        assert_eq!(cells.len(), 50);
+        assert!(cells.contains(&(0, 2, 4, 1.0)));
+        assert_eq!(cells.iter().filter(|cell| cell.3 == 1.0).count(), 1);
+    }
+}
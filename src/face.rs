@@ -0,0 +1,429 @@
+use {
+    crate::{
+        arena::Arena,
+        game::{ball_collision_system, paddle_movement_system, Ball, GameEvent, Paddle, SPRITE_Z},
+        state::Simulation,
+        theme::Theme,
+        tween::{Ease, Lens, Tween, Tweens},
+        versus::Player,
+    },
+    bevy::{
+        prelude::*,
+        render::{mesh::Indices, render_resource::PrimitiveTopology},
+        sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    },
+    rand::prelude::random,
+};
+
+const MOUTH_SEGMENTS: usize = 16;
+const EYE_DIST: f32 = 30.0;
+const EYE_SCALE: f32 = 0.25;
+/// radii of `eye.png` and `black-eye.png` in pixels
+const EYE_RADIUS: f32 = 30.0;
+const PUPIL_RADIUS: f32 = 12.0;
+const BLINK_DURATION: f32 = 0.15;
+/// how far the mouth sits below the center of the paddle
+pub const MOUTH_DROP: f32 = 9.0;
+
+/// the ball speed where the paddle starts to squint
+const FAST_BALL: f32 = 450.0;
+/// the height above the bottom wall where the paddle starts to get scared
+const DANGER_ZONE: f32 = 160.0;
+
+/// The eyes and the mouth of the paddles, following the ball and the game
+pub struct FacePlugin;
+
+impl Plugin for FacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (
+                paddle_parts_system
+                    .after(paddle_movement_system)
+                    .after(ball_collision_system),
+                paddle_eye_system.after(paddle_parts_system),
+                paddle_expression_system.after(ball_collision_system),
+            )
+                .in_set(Simulation),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Expression {
+    #[default]
+    Neutral,
+    Happy,
+    Shocked,
+    Sad,
+    Ecstatic,
+}
+
+impl Expression {
+    /// How long the expression lasts before the face calms down, in seconds
+    pub fn duration(self) -> f32 {
+        match self {
+            Expression::Neutral => f32::INFINITY,
+            Expression::Happy => 1.0,
+            Expression::Shocked => 0.8,
+            Expression::Sad => 1.5,
+            Expression::Ecstatic => f32::INFINITY,
+        }
+    }
+    /// A stronger expression takes over a weaker one that is still showing.
+    pub fn priority(self) -> usize {
+        match self {
+            Expression::Neutral => 0,
+            Expression::Happy => 1,
+            Expression::Shocked => 2,
+            Expression::Sad => 3,
+            Expression::Ecstatic => 4,
+        }
+    }
+    pub fn mouth(self) -> MouthShape {
+        match self {
+            Expression::Neutral => MouthShape::new(26.0, 0.0, 0.0),
+            Expression::Happy => MouthShape::new(34.0, 4.0, 0.0),
+            Expression::Shocked => MouthShape::new(12.0, 0.0, 5.0),
+            Expression::Sad => MouthShape::new(26.0, -4.0, 0.0),
+            Expression::Ecstatic => MouthShape::new(40.0, 6.0, 5.0),
+        }
+    }
+}
+
+/// The outline of a mouth
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MouthShape {
+    pub width: f32,
+    /// how far the middle sinks below the corners, negative for a frown
+    pub curve: f32,
+    /// how far the lips part in the middle
+    pub open: f32,
+}
+
+impl MouthShape {
+    pub const fn new(width: f32, curve: f32, open: f32) -> Self {
+        MouthShape { width, curve, open }
+    }
+    pub fn lerp(self, other: MouthShape, t: f32) -> Self {
+        MouthShape {
+            width: self.width + (other.width - self.width) * t,
+            curve: self.curve + (other.curve - self.curve) * t,
+            open: self.open + (other.open - self.open) * t,
+        }
+    }
+    pub fn distance(self, other: MouthShape) -> f32 {
+        (self.width - other.width).abs()
+            + (self.curve - other.curve).abs()
+            + (self.open - other.open).abs()
+    }
+}
+
+/// Build a band along a parabola, thickening in the middle as the mouth opens.
+pub fn mouth_mesh(shape: MouthShape) -> Mesh {
+    const LIP: f32 = 1.5;
+    let mut positions: Vec<[f32; 3]> = Vec::with_capacity(2 * (MOUTH_SEGMENTS + 1));
+    let mut uvs: Vec<[f32; 2]> = Vec::with_capacity(2 * (MOUTH_SEGMENTS + 1));
+    for i in 0..=MOUTH_SEGMENTS {
+        let u = i as f32 / MOUTH_SEGMENTS as f32;
+        let s = 2.0 * u - 1.0;
+        let bulge = 1.0 - s * s;
+        let x = s * shape.width / 2.0;
+        let y = -shape.curve * bulge;
+        let half = LIP + shape.open * bulge;
+        positions.push([x, y + half, 0.0]);
+        positions.push([x, y - half, 0.0]);
+        uvs.push([u, 0.0]);
+        uvs.push([u, 1.0]);
+    }
+    let mut indices: Vec<u32> = Vec::with_capacity(6 * MOUTH_SEGMENTS);
+    for i in 0..MOUTH_SEGMENTS as u32 {
+        let k = 2 * i;
+        indices.extend_from_slice(&[k, k + 1, k + 2, k + 2, k + 1, k + 3]);
+    }
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// The socket of an eye, following the paddle
+#[derive(Component)]
+pub struct PaddleEye {
+    pub is_left: bool,
+    /// the paddle with the [`Face`] the eye belongs to
+    pub face: Entity,
+}
+
+#[derive(Component)]
+pub struct EyeWhite {
+    /// whose paddle it is on, for its tint
+    pub player: Player,
+}
+
+#[derive(Component)]
+pub struct Pupil;
+
+#[derive(Component)]
+pub struct Mouth {
+    pub face: Entity,
+}
+
+/// The expression of the paddle, changing smoothly
+#[derive(Component)]
+pub struct Face {
+    /// seconds until the next blink
+    next_blink: f32,
+    /// seconds since the current blink began
+    blinking: Option<f32>,
+    /// how far the eyes are open, narrowed when the ball is fast
+    openness: f32,
+    /// the size of the eyes, widened when the ball is about to drop
+    wideness: f32,
+    expression: Expression,
+    /// seconds since the current expression appeared
+    expression_age: f32,
+    /// the mouth on its way to the shape of the current expression
+    mouth: MouthShape,
+}
+
+impl Default for Face {
+    fn default() -> Self {
+        Face {
+            next_blink: 2.0 + 4.0 * random::<f32>(),
+            blinking: None,
+            openness: 1.0,
+            wideness: 1.0,
+            expression: Expression::Neutral,
+            expression_age: 0.0,
+            mouth: Expression::Neutral.mouth(),
+        }
+    }
+}
+
+/// Spawn the eyes and the mouth of the paddle `face` of `player` at `position`.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_face(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    theme: &Theme,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    face: Entity,
+    player: Player,
+    position: Vec2,
+) {
+    // paddle eyes: a socket carrying the white of the eye and its pupil
+    for is_left in [true, false] {
+        let x = if is_left { -EYE_DIST } else { EYE_DIST };
+        commands
+            .spawn(SpatialBundle::from_transform(Transform::from_xyz(
+                position.x + x,
+                position.y,
+                SPRITE_Z + 0.1,
+            )))
+            .insert(PaddleEye { is_left, face })
+            .with_children(|socket| {
+                socket
+                    .spawn(SpriteBundle {
+                        transform: Transform::from_scale(Vec3::new(EYE_SCALE, EYE_SCALE, 1.0)),
+                        texture: asset_server.load("sprites/eye.png"),
+                        sprite: Sprite {
+                            color: theme.player_eyes(player),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .insert(EyeWhite { player });
+                socket
+                    .spawn(SpriteBundle {
+                        transform: Transform {
+                            translation: Vec3::new(0.0, 0.0, 0.1),
+                            scale: Vec3::new(EYE_SCALE, EYE_SCALE, 1.0),
+                            ..Default::default()
+                        },
+                        texture: asset_server.load("sprites/black-eye.png"),
+                        ..Default::default()
+                    })
+                    .insert(Pupil);
+            });
+    }
+    // paddle mouth
+    commands
+        .spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(mouth_mesh(Expression::Neutral.mouth())).into(),
+            material: materials.add(ColorMaterial::from(theme.mouth)),
+            transform: Transform::from_xyz(position.x, position.y - MOUTH_DROP, SPRITE_Z + 0.1),
+            ..Default::default()
+        })
+        .insert(Mouth { face });
+}
+
+/// Keep the eyes and the mouth on the paddle, and pop the eyes when the ball touches it.
+#[allow(clippy::type_complexity)]
+fn paddle_parts_system(
+    mut commands: Commands,
+    mut events: EventReader<GameEvent>,
+    paddle_query: Query<(&Paddle, &Transform), With<Face>>,
+    mut parts: Query<
+        (Entity, &mut Transform, Option<&PaddleEye>, Option<&Mouth>),
+        (Or<(With<PaddleEye>, With<Mouth>)>, Without<Face>),
+    >,
+) {
+    let hits: Vec<Player> = events
+        .iter()
+        .filter_map(|event| match *event {
+            GameEvent::PaddleHit { player } => Some(player),
+            _ => None,
+        })
+        .collect();
+    for (entity, mut trans, eye, mouth) in parts.iter_mut() {
+        let face = match (eye, mouth) {
+            (Some(eye), _) => eye.face,
+            (_, Some(mouth)) => mouth.face,
+            _ => continue,
+        };
+        let Ok((paddle, transform)) = paddle_query.get(face) else { continue; };
+        let p_pos = transform.translation.x;
+        let Some(eye) = eye else {
+            trans.translation.x = p_pos;
+            continue;
+        };
+        trans.translation.x = if eye.is_left {
+            p_pos - EYE_DIST
+        } else {
+            p_pos + EYE_DIST
+        };
+        if hits.contains(&paddle.player) {
+            commands.entity(entity).insert(Tweens::new(Tween::new(
+                Lens::Scale {
+                    start: Vec3::new(3.0, 3.0, 1.0),
+                    end: Vec3::ONE,
+                },
+                Ease::ExpoOut,
+                0.2,
+            )));
+        }
+    }
+}
+
+/// Let the eyes follow the ball, blink now and then, squint at a fast ball and widen in danger.
+#[allow(clippy::type_complexity)]
+fn paddle_eye_system(
+    time: Res<Time>,
+    arena: Res<Arena>,
+    mut paddle_query: Query<(Entity, &mut Face), With<Paddle>>,
+    ball_query: Query<(&Ball, &GlobalTransform)>,
+    eye_query: Query<(&PaddleEye, &GlobalTransform, &Children)>,
+    mut parts: ParamSet<(
+        Query<&mut Transform, With<EyeWhite>>,
+        Query<(&mut Transform, &mut Visibility), With<Pupil>>,
+    )>,
+) {
+    let dt = time.delta_seconds();
+    let Ok((ball, ball_transform)) = ball_query.get_single() else { return; };
+    let ball_pos = ball_transform.translation().truncate();
+
+    // squint at a fast ball, widen the eyes when it is falling close to the bottom
+    let speed = ball.velocity.length();
+    let squint = ((speed - FAST_BALL) / FAST_BALL).clamp(0.0, 0.6);
+    let height = ball_pos.y - arena.inner().min.y;
+    let danger = if ball.velocity.y < 0.0 {
+        (1.0 - height / DANGER_ZONE).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let smooth = 1.0 - (-10.0 * dt).exp();
+
+    // each face blinks on its own
+    for (entity, mut face) in paddle_query.iter_mut() {
+        let mut lid = 1.0;
+        if let Some(t) = face.blinking {
+            let t = t + dt;
+            if t < BLINK_DURATION {
+                lid = 1.0 - (std::f32::consts::PI * t / BLINK_DURATION).sin();
+                face.blinking = Some(t);
+            } else {
+                face.blinking = None;
+                face.next_blink = 2.0 + 4.0 * random::<f32>();
+            }
+        } else {
+            face.next_blink -= dt;
+            if face.next_blink < 0.0 {
+                face.blinking = Some(0.0);
+            }
+        }
+
+        face.openness += (1.0 - squint - face.openness) * smooth;
+        face.wideness += (1.0 + 0.5 * danger - face.wideness) * smooth;
+        let openness = face.openness * lid;
+        let wideness = face.wideness;
+
+        for (_, socket, children) in eye_query.iter().filter(|(eye, ..)| eye.face == entity) {
+            // look at the ball in the socket space
+            let to_ball = ball_pos - socket.translation().truncate();
+            let reach = EYE_SCALE * (EYE_RADIUS * wideness - PUPIL_RADIUS);
+            let offset = to_ball.normalize_or_zero() * reach.max(0.0);
+            for child in children.iter() {
+                if let Ok(mut white) = parts.p0().get_mut(*child) {
+                    white.scale =
+                        Vec3::new(EYE_SCALE * wideness, EYE_SCALE * wideness * openness, 1.0);
+                }
+                if let Ok((mut pupil, mut visibility)) = parts.p1().get_mut(*child) {
+                    pupil.translation.x = offset.x;
+                    pupil.translation.y = offset.y * openness;
+                    pupil.scale = Vec3::new(EYE_SCALE, EYE_SCALE * openness, 1.0);
+                    *visibility = if openness < 0.2 {
+                        Visibility::Hidden
+                    } else {
+                        Visibility::Inherited
+                    };
+                }
+            }
+        }
+    }
+}
+
+/// Show the feelings of the paddles about the game on their mouths.
+fn paddle_expression_system(
+    time: Res<Time>,
+    mut events: EventReader<GameEvent>,
+    mut face_query: Query<(Entity, &mut Face), With<Paddle>>,
+    mouth_query: Query<(&Mouth, &Mesh2dHandle)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let dt = time.delta_seconds();
+    let events: Vec<GameEvent> = events.iter().copied().collect();
+    for (entity, mut face) in face_query.iter_mut() {
+        face.expression_age += dt;
+        if face.expression.duration() < face.expression_age {
+            face.expression = Expression::Neutral;
+        }
+        for event in &events {
+            let expression = match *event {
+                GameEvent::BrickHit { combo } if 1 < combo => Expression::Happy,
+                GameEvent::BrickHit { .. } | GameEvent::PaddleHit { .. } => continue,
+                GameEvent::NearMiss => Expression::Shocked,
+                GameEvent::Penalty => Expression::Sad,
+                GameEvent::LevelCleared => Expression::Ecstatic,
+                GameEvent::GameOver => Expression::Sad,
+            };
+            if face.expression.priority() <= expression.priority() {
+                face.expression = expression;
+                face.expression_age = 0.0;
+            }
+        }
+        let target = face.expression.mouth();
+        if face.mouth.distance(target) < 0.01 {
+            continue;
+        }
+        face.mouth = face.mouth.lerp(target, 1.0 - (-12.0 * dt).exp());
+        for (_, handle) in mouth_query.iter().filter(|(mouth, _)| mouth.face == entity) {
+            if let Some(mesh) = meshes.get_mut(&handle.0) {
+                *mesh = mouth_mesh(face.mouth);
+            }
+        }
+    }
+}
@@ -0,0 +1,733 @@
+use {
+    crate::{
+        arena::{Arena, Wall, WallSide},
+        coop::Coop,
+        hud::{Scoreboard, TextBonus},
+        input::{ActionState, InputAction},
+        level::{BrickKind, Level, LevelBrick},
+        popup::ScorePopupEvent,
+        settings::Settings,
+        stats::Statistics,
+        theme::{ActiveTheme, Theme},
+        tween::{Ease, Lens, Motion, OnComplete, Tween, Tweens},
+        versus::{Player, Versus},
+    },
+    bevy::{
+        prelude::*,
+        sprite::collide_aabb::{collide, Collision},
+        time::TimeUpdateStrategy,
+    },
+    rand::{rngs::StdRng, Rng, SeedableRng},
+    std::time::{Duration, Instant},
+};
+
+/// The length of a tick of the simulation in seconds
+pub const TIME_STEP: f32 = 1.0 / 60.0;
+pub const SPRITE_Z: f32 = 1.0;
+pub const BALL_SIZE: f32 = 20.0;
+/// the speed of the ball at the start, before the difficulty applies
+pub const BALL_SPEED: f32 = 400.0;
+/// where the ball starts, below the bricks
+pub const BALL_START: Vec2 = Vec2::new(0.0, -50.0);
+pub const PADDLE_SIZE: Vec2 = Vec2::new(120.0, 30.0);
+/// seconds the walls take to slide in, and each brick to fly in once the walls are there
+pub const WALL_SLIDE: f32 = 0.5;
+const BRICK_FLIGHT: f32 = 0.6;
+/// seconds between the starts of two bricks flying in
+const BRICK_STAGGER: f32 = 0.025;
+
+#[derive(Component)]
+pub struct Paddle {
+    pub speed: f32,
+    pub player: Player,
+    /// the wall behind the paddle, costing a life when the ball reaches it
+    pub goal: WallSide,
+}
+
+#[derive(Component)]
+pub struct Ball {
+    pub velocity: Vec3,
+    pub rotation: f32,
+    /// the player whose paddle touched it last, credited for the bricks it breaks
+    pub last_touch: Player,
+    /// whether it rests on the paddle of the first player, waiting for [`InputAction::Launch`]
+    pub serving: bool,
+}
+
+#[derive(Component)]
+pub struct Brick {
+    pub hit: bool,
+    /// its cell, counting rows from the bottom, or its band in a layout, its kind and the hits
+    /// it still takes
+    pub spec: LevelBrick,
+    /// the rows of its level or the bands of its layout, for the colors of the theme
+    pub rows: usize,
+}
+
+/// The randomness of the game, seeded so that a game replays the same from the same seed
+#[derive(Resource)]
+pub struct GameRng(pub StdRng);
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng(StdRng::from_entropy())
+    }
+}
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        GameRng(StdRng::seed_from_u64(seed))
+    }
+}
+
+/// Seconds until the ball is released, while the level flies in
+#[derive(Debug, Default, Resource)]
+pub struct LevelIntro(pub f32);
+
+/// What happened in the game, for everything reacting beyond the physics
+#[derive(Clone, Copy, Debug)]
+pub enum GameEvent {
+    BrickHit {
+        combo: usize,
+    },
+    /// the ball touched the paddle of `player`
+    PaddleHit {
+        player: Player,
+    },
+    /// the paddle caught the ball with its very edge
+    NearMiss,
+    Penalty,
+    LevelCleared,
+    /// the ball hit the bottom wall with the last life
+    GameOver,
+}
+
+#[derive(Component, Eq, PartialEq)]
+pub enum Collider {
+    Solid,
+    Paddle,
+}
+
+/// A wall of the arena, bouncing the ball back
+pub fn wall_bundle(arena: &Arena, side: WallSide, theme: &Theme) -> impl Bundle {
+    let rect = arena.wall(side);
+    (
+        SpriteBundle {
+            transform: Transform {
+                translation: rect.center().extend(SPRITE_Z),
+                scale: rect.size().extend(1.0),
+                ..Default::default()
+            },
+            sprite: Sprite {
+                color: theme.walls,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Wall(side),
+        Collider::Solid,
+    )
+}
+
+/// The paddle of `player` at `x` in front of the `goal` wall, the bottom or the top one
+pub fn paddle_bundle(
+    arena: &Arena,
+    theme: &Theme,
+    player: Player,
+    goal: WallSide,
+    x: f32,
+) -> impl Bundle {
+    let y = match goal {
+        WallSide::Top => arena.top_paddle_y(),
+        _ => arena.paddle_y(),
+    };
+    (
+        SpriteBundle {
+            transform: Transform {
+                translation: Vec3::new(x, y, SPRITE_Z),
+                scale: PADDLE_SIZE.extend(0.0),
+                ..Default::default()
+            },
+            sprite: Sprite {
+                color: theme.player_paddle(player),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Paddle {
+            speed: 500.0,
+            player,
+            goal,
+        },
+        Collider::Paddle,
+    )
+}
+
+/// The ball at `position`, heading down to the right, or resting there until served when
+/// `serving`
+pub fn ball_bundle(theme: &Theme, position: Vec2, serving: bool) -> impl Bundle {
+    (
+        SpriteBundle {
+            transform: Transform {
+                scale: Vec3::new(BALL_SIZE, BALL_SIZE, 0.0),
+                translation: position.extend(SPRITE_Z),
+                ..Default::default()
+            },
+            sprite: Sprite {
+                color: theme.ball,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Ball {
+            velocity: if serving {
+                Vec3::ZERO
+            } else {
+                BALL_SPEED * Vec3::new(0.5, -0.5, 0.0).normalize()
+            },
+            rotation: 0.0,
+            last_touch: Player::One,
+            serving,
+        },
+    )
+}
+
+/// The velocity of the ball served by a paddle moving along `axis`: up, off to the side the
+/// paddle is moving to.
+pub fn serve_velocity(axis: f32) -> Vec3 {
+    let side = if axis < 0.0 { -0.5 } else { 0.5 };
+    BALL_SPEED * Vec3::new(side, 0.5, 0.0).normalize()
+}
+
+/// Where the ball rests on top of the paddle at `paddle` before it is served
+pub fn serve_position(paddle: Vec2) -> Vec2 {
+    paddle + Vec2::new(0.0, (PADDLE_SIZE.y + BALL_SIZE) / 2.0 + 1.0)
+}
+
+/// The clock of a game ticking in lockstep with a program driving it, a [`TIME_STEP`] per frame
+pub fn lockstep_time() -> TimeUpdateStrategy {
+    TimeUpdateStrategy::ManualInstant(Instant::now())
+}
+
+/// Move a lockstep clock on by a tick, before the time is updated.
+pub fn lockstep_time_system(mut strategy: ResMut<TimeUpdateStrategy>) {
+    if let TimeUpdateStrategy::ManualInstant(instant) = strategy.as_mut() {
+        *instant += Duration::from_secs_f32(TIME_STEP);
+    }
+}
+
+/// Spawn the bricks of `level` flying in from above one after the other, returning how long
+/// they take to land.
+pub fn spawn_bricks(
+    commands: &mut Commands,
+    arena: &Arena,
+    theme: &Theme,
+    level: &Level,
+    scoreboard: &mut Scoreboard,
+) -> f32 {
+    let cell_size = arena.brick_size(level.rows, level.columns, level.spacing);
+    // the bricks of the grid, then the ones of the layouts around the middle of the brick area
+    let mut bricks: Vec<_> = level
+        .bricks
+        .iter()
+        .map(|brick| {
+            let position = arena.brick_position(brick.row, brick.column, cell_size, level.spacing);
+            (brick.clone(), level.rows, position, cell_size)
+        })
+        .collect();
+    let middle = arena.brick_area().center();
+    for layout in &level.layouts {
+        let bands = layout.bands();
+        bricks.extend(
+            layout
+                .bricks()
+                .into_iter()
+                .map(|(brick, offset)| (brick, bands, middle + offset, layout.brick)),
+        );
+    }
+    let mut landed = WALL_SLIDE;
+    // the bottom rows first, so that bricks don't fly through landed ones
+    bricks.sort_by(|a, b| a.2.y.total_cmp(&b.2.y).then(a.2.x.total_cmp(&b.2.x)));
+    for (index, (brick, rows, position, size)) in bricks.into_iter().enumerate() {
+        let brick_position = position.extend(SPRITE_Z);
+        let start = brick_position + Vec3::new(0.0, arena.viewport.y, 0.0);
+        let delay = WALL_SLIDE + index as f32 * BRICK_STAGGER;
+        landed = delay + BRICK_FLIGHT;
+        // brick
+        commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: brick.color(theme, rows),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: start,
+                    scale: size.extend(1.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            // .insert(Collider::Scorable)
+            .insert(Brick {
+                hit: false,
+                spec: brick,
+                rows,
+            })
+            .insert(Tweens::new(
+                Tween::new(
+                    Lens::Translation {
+                        start,
+                        end: brick_position,
+                    },
+                    Ease::BackOut,
+                    BRICK_FLIGHT,
+                )
+                .with_delay(delay),
+            ));
+    }
+    scoreboard.remain_bricks = level.breakable_count();
+    landed
+}
+
+pub fn paddle_movement_system(
+    actions: Res<ActionState>,
+    arena: Res<Arena>,
+    mut paddle_query: Query<(&Paddle, &mut Transform)>,
+) {
+    let mut paddles: Vec<_> = paddle_query.iter_mut().collect();
+    let from: Vec<f32> = paddles.iter().map(|(_, t)| t.translation.x).collect();
+    for (paddle, transform) in paddles.iter_mut() {
+        let (min_x, max_x) = arena.paddle_range(transform.scale.x);
+        let translation = &mut transform.translation;
+        // move the paddle horizontally, the second player on the keys only
+        match (paddle.player, actions.pointer_x) {
+            (Player::One, Some(x)) => translation.x = x,
+            (Player::One, None) => translation.x += actions.axis * paddle.speed * TIME_STEP,
+            (Player::Two, _) => translation.x += actions.second_axis * paddle.speed * TIME_STEP,
+        }
+        // bound the paddle within the walls
+        translation.x = translation.x.clamp(min_x, max_x);
+    }
+    // paddles in front of the same wall bump into each other instead of passing through
+    if let [(a, ta), (b, tb)] = &mut paddles[..] {
+        if a.goal == b.goal {
+            let gap = (ta.scale.x + tb.scale.x) / 2.0;
+            let mut to = [ta.translation.x, tb.translation.x];
+            bump_paddles([from[0], from[1]], &mut to, gap);
+            ta.translation.x = to[0];
+            tb.translation.x = to[1];
+        }
+    }
+}
+
+/// Push two paddles moved from `from` to `to` back until `gap` separates their centers, each
+/// by its share of the way they came toward each other, the left one staying on the left.
+fn bump_paddles(from: [f32; 2], to: &mut [f32; 2], gap: f32) {
+    let (left, right) = if from[0] <= from[1] { (0, 1) } else { (1, 0) };
+    let overlap = gap - (to[right] - to[left]);
+    if overlap <= 0.0 {
+        return;
+    }
+    let came_left = (to[left] - from[left]).max(0.0);
+    let came_right = (from[right] - to[right]).max(0.0);
+    let share = if 0.0 < came_left + came_right {
+        came_left / (came_left + came_right)
+    } else {
+        0.5
+    };
+    to[left] -= overlap * share;
+    to[right] += overlap * (1.0 - share);
+}
+
+pub fn ball_movement_system(
+    time: Res<Time>,
+    motion: Res<Motion>,
+    settings: Res<Settings>,
+    actions: Res<ActionState>,
+    mut intro: ResMut<LevelIntro>,
+    mut ball_query: Query<(&mut Ball, &mut Transform, Option<&Tweens>)>,
+    paddle_query: Query<(&Paddle, &Transform), Without<Ball>>,
+) {
+    // the ball rides on the paddle of the first player until served
+    let rest = paddle_query
+        .iter()
+        .find(|(paddle, _)| paddle.player == Player::One)
+        .map(|(_, transform)| serve_position(transform.translation.truncate()));
+    for (ball, mut transform, _) in ball_query.iter_mut() {
+        if let (true, Some(rest)) = (ball.serving, rest) {
+            transform.translation = rest.extend(transform.translation.z);
+        }
+    }
+    // the ball waits for the bricks to land
+    if 0.0 < intro.0 {
+        intro.0 -= time.delta_seconds();
+        return;
+    }
+    let (mut ball, mut transform, tweens) = ball_query.single_mut();
+    if ball.serving {
+        if !actions.just_pressed(InputAction::Launch) {
+            return;
+        }
+        ball.velocity = serve_velocity(actions.axis);
+        ball.serving = false;
+    }
+    let vel = ball.velocity * settings.difficulty.ball_speed() * TIME_STEP;
+    transform.translation += vel;
+    // transform.rotation = transform.rotation.add(Quat::from_rotation_x(0.01));
+    ball.rotation += 8.0 * motion.0 * TIME_STEP;
+    transform.rotation = Quat::from_rotation_z(ball.rotation);
+    // accelerate while the ball is still swollen from a bounce
+    if tweens.is_some_and(|t| t.is_running()) {
+        transform.translation += 0.3 * vel;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn ball_collision_system(
+    mut commands: Commands,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut versus: ResMut<Versus>,
+    mut coop: ResMut<Coop>,
+    mut statistics: ResMut<Statistics>,
+    mut ball_query: Query<(Entity, &mut Ball, &Transform)>,
+    mut brick_query: Query<(Entity, &mut Brick, &mut Sprite, &Transform)>,
+    mut bonus_query: Query<&mut TextBonus>,
+    mut popups: EventWriter<ScorePopupEvent>,
+    mut game_events: EventWriter<GameEvent>,
+    collider_query: Query<(&Collider, &Transform, Option<&Paddle>, Option<&Wall>)>,
+    theme: Res<ActiveTheme>,
+    mut rng: ResMut<GameRng>,
+) {
+    let (ball_entity, mut ball, ball_transform) = ball_query.single_mut();
+    let ball_size = ball_transform.scale.truncate();
+    let Ball {
+        velocity,
+        last_touch,
+        ..
+    } = &mut *ball;
+    let mut penalty = 0;
+    // the player whose paddle the ball got past
+    let mut missed = Player::One;
+    let mut collided = false;
+    // the player whose paddle the ball touched
+    let mut paddle_hit = None;
+    // the combo carries over when the ball comes down from the bricks to the other player
+    let kept = scoreboard.keeping;
+
+    // check collision with walls
+    for (collider, transform, paddle, wall) in collider_query.iter() {
+        let collision = collide(
+            ball_transform.translation,
+            ball_size,
+            transform.translation,
+            transform.scale.truncate(),
+        );
+        let Some(collision) = collision else { continue; };
+        collided = true;
+        scoreboard.keeping = false;
+
+        // reflect the ball when it collides
+        let mut reflect_x = false;
+        let mut reflect_y = false;
+
+        // only reflect if the ball's velocity is going in the opposite direction of the
+        // collision
+        match collision {
+            Collision::Left => reflect_x = velocity.x > 0.0,
+            Collision::Right => reflect_x = velocity.x < 0.0,
+            Collision::Top => reflect_y = velocity.y < 0.0,
+            Collision::Bottom => reflect_y = velocity.y > 0.0,
+            Collision::Inside => (),
+        }
+
+        // reflect velocity on the x-axis if we hit something on the x-axis
+        if reflect_x {
+            velocity.x = -velocity.x + rng.0.gen::<f32>() * 2.0;
+        }
+
+        // reflect velocity on the y-axis if we hit something on the y-axis
+        if reflect_y {
+            velocity.y = -velocity.y + rng.0.gen::<f32>() * 2.0;
+        }
+
+        if let (Collider::Paddle, Some(paddle)) = (collider, paddle) {
+            // the side of the paddle facing the bricks, the top one above the bottom wall
+            let (front, back) = match paddle.goal {
+                WallSide::Top => (Collision::Bottom, Collision::Top),
+                _ => (Collision::Top, Collision::Bottom),
+            };
+            if collision == back {
+                if !versus.active {
+                    penalty = 2;
+                    missed = paddle.player;
+                }
+            } else if collision == front && reflect_y {
+                if coop.active {
+                    if paddle.player != *last_touch {
+                        scoreboard.keeping = kept;
+                    }
+                    coop.shot(paddle.player);
+                }
+                *last_touch = paddle.player;
+                statistics.shot();
+                if 0.35 * transform.scale.x
+                    < (ball_transform.translation.x - transform.translation.x).abs()
+                {
+                    game_events.send(GameEvent::NearMiss);
+                }
+            }
+            paddle_hit = Some(paddle.player);
+        } else if let Collider::Solid = *collider {
+            // break if this collide is on a solid, otherwise continue check
+            // whether a solid is also in collision
+            // the bottom wall, or the top one behind the second player in versus
+            let goal = match collision {
+                Collision::Top if reflect_y => Some(Player::One),
+                Collision::Bottom if reflect_y && versus.active => wall
+                    .filter(|wall| wall.0 == WallSide::Top)
+                    .map(|_| Player::Two),
+                _ => None,
+            };
+            if let Some(player) = goal {
+                scoreboard.brick_in_row = 1;
+                scoreboard.keeping = false;
+                penalty = penalty.max(1);
+                missed = player;
+                // in co-op, the player closest to where the ball got through
+                let ball_x = ball_transform.translation.x;
+                let nearest = collider_query
+                    .iter()
+                    .filter_map(|(_, transform, paddle, _)| {
+                        paddle.map(|p| (p.player, (transform.translation.x - ball_x).abs()))
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                if let (true, Some((player, _))) = (coop.active, nearest) {
+                    missed = player;
+                }
+            }
+            break;
+        }
+    }
+    match penalty {
+        _ if 0 == scoreboard.remain_bricks || 0 == scoreboard.lives => (),
+        _ if versus.winner.is_some() => (),
+        1 if versus.active => {
+            versus.miss(missed);
+            statistics.penalty();
+            game_events.send(GameEvent::Penalty);
+            if versus.winner.is_some() {
+                game_events.send(GameEvent::GameOver);
+            }
+        }
+        2 => {
+            scoreboard.score /= 2;
+            statistics.penalty();
+            if coop.active {
+                coop.penalty(missed);
+            }
+            game_events.send(GameEvent::Penalty);
+        }
+        1 => {
+            // the ball reached the bottom wall
+            scoreboard.score = scoreboard.score.saturating_sub(1);
+            scoreboard.lives -= 1;
+            statistics.penalty();
+            if coop.active {
+                coop.penalty(missed);
+            }
+            game_events.send(GameEvent::Penalty);
+            if 0 == scoreboard.lives {
+                game_events.send(GameEvent::GameOver);
+            }
+        }
+        _ => (),
+    }
+    // check collision with brick
+    for (brick_entity, mut brick, mut sprite, transform) in brick_query.iter_mut() {
+        let collision = collide(
+            ball_transform.translation,
+            ball_size,
+            transform.translation,
+            transform.scale.truncate(),
+        );
+        let Some(collision) = collision else { continue; };
+        if brick.hit {
+            continue;
+        }
+        collided = true;
+        let breaks = brick.spec.kind.is_breakable() && brick.spec.hp <= 1;
+        if breaks && 0 < scoreboard.remain_bricks {
+            if scoreboard.keeping {
+                scoreboard.brick_in_row += 1;
+                if 1 < scoreboard.brick_in_row {
+                    if let Ok(mut bonus) = bonus_query.get_single_mut() {
+                        bonus.row = scoreboard.brick_in_row;
+                    }
+                }
+            }
+            scoreboard.keeping = true;
+            if versus.active {
+                versus.scores[last_touch.index()] += scoreboard.brick_in_row;
+            } else {
+                scoreboard.score += scoreboard.brick_in_row;
+            }
+            popups.send(ScorePopupEvent {
+                position: transform.translation.truncate(),
+                points: scoreboard.brick_in_row,
+            });
+            scoreboard.remain_bricks -= 1;
+            if brick.spec.kind == BrickKind::Bonus && versus.active {
+                versus.lives[last_touch.index()] += 1;
+            } else if brick.spec.kind == BrickKind::Bonus {
+                scoreboard.lives += 1;
+            }
+            statistics.brick_hit(scoreboard.brick_in_row);
+            if coop.active {
+                coop.brick_hit(*last_touch, scoreboard.brick_in_row);
+            }
+            game_events.send(GameEvent::BrickHit {
+                combo: scoreboard.brick_in_row,
+            });
+            if versus.active {
+                let decided = versus.winner.is_some();
+                versus.brick_broken(Player::side(transform.translation.y));
+                if !decided && versus.winner.is_some() {
+                    game_events.send(GameEvent::GameOver);
+                }
+            } else if 0 == scoreboard.remain_bricks {
+                game_events.send(GameEvent::LevelCleared);
+            }
+        }
+        if breaks {
+            // knock the brick away along the ball, wobbling and shrinking until it vanishes
+            brick.hit = true;
+            let start = transform.translation;
+            commands.entity(brick_entity).insert(
+                Tweens::new(
+                    Tween::new(
+                        Lens::Translation {
+                            start,
+                            end: start + 0.17 * *velocity,
+                        },
+                        Ease::ExpoOut,
+                        0.75,
+                    )
+                    .on_complete(OnComplete::Despawn),
+                )
+                .with(Tween::new(
+                    Lens::Scale {
+                        start: transform.scale,
+                        end: transform.scale * 0.64,
+                    },
+                    Ease::Linear,
+                    0.75,
+                ))
+                .with(Tween::new(
+                    Lens::Wobble { amplitude: 0.4 },
+                    Ease::Linear,
+                    0.75,
+                )),
+            );
+        } else {
+            // steel only shakes, the other bricks crack and darken
+            if brick.spec.kind.is_breakable() {
+                brick.spec.hp -= 1;
+                sprite.color = brick.spec.color(&theme.0, brick.rows);
+            }
+            commands.entity(brick_entity).insert(Tweens::new(Tween::new(
+                Lens::Wobble { amplitude: 0.15 },
+                Ease::Linear,
+                0.3,
+            )));
+        }
+
+        // reflect the ball when it collides
+        let mut reflect_x = false;
+        let mut reflect_y = false;
+
+        // only reflect if the ball's velocity is going in the opposite direction of the
+        // collision
+        match collision {
+            Collision::Left => reflect_x = velocity.x > 0.0,
+            Collision::Right => reflect_x = velocity.x < 0.0,
+            Collision::Top => reflect_y = velocity.y < 0.0,
+            Collision::Bottom => reflect_y = velocity.y > 0.0,
+            Collision::Inside => (),
+        }
+
+        // reflect velocity on the x-axis if we hit something on the x-axis
+        if reflect_x {
+            velocity.x = -velocity.x;
+        }
+
+        // reflect velocity on the y-axis if we hit something on the y-axis
+        if reflect_y {
+            velocity.y = -velocity.y;
+        }
+    }
+    if collided {
+        commands.entity(ball_entity).insert(Tweens::new(Tween::new(
+            Lens::Scale {
+                start: Vec3::new(2.0 * BALL_SIZE, 2.0 * BALL_SIZE, 0.0),
+                end: Vec3::new(BALL_SIZE, BALL_SIZE, 0.0),
+            },
+            Ease::ExpoOut,
+            1.0,
+        )));
+    }
+    if let Some(player) = paddle_hit {
+        game_events.send(GameEvent::PaddleHit { player });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAP: f32 = PADDLE_SIZE.x;
+
+    #[test]
+    fn paddles_apart_stay_put() {
+        let mut to = [-100.0, 100.0];
+        bump_paddles([-150.0, 150.0], &mut to, GAP);
+        assert_eq!(to, [-100.0, 100.0]);
+    }
+
+    #[test]
+    fn paddles_bump_by_the_way_they_came() {
+        // a paddle running into a still one stops against it
+        let mut to = [100.0, 200.0];
+        bump_paddles([0.0, 200.0], &mut to, GAP);
+        assert_eq!(to, [80.0, 200.0]);
+        // two paddles running into each other both step back
+        let mut to = [60.0, 140.0];
+        bump_paddles([0.0, 200.0], &mut to, GAP);
+        assert_eq!(to, [40.0, 160.0]);
+        // the second player on the left
+        let mut to = [200.0, 100.0];
+        bump_paddles([200.0, 0.0], &mut to, GAP);
+        assert_eq!(to, [200.0, 80.0]);
+    }
+
+    #[test]
+    fn paddles_never_overlap() {
+        let steps = [-300.0, -90.0, -10.0, 0.0, 10.0, 90.0, 300.0];
+        for from in [[-200.0, 200.0], [0.0, 120.0], [50.0, -70.0], [10.0, 10.0]] {
+            for a in steps {
+                for b in steps {
+                    let mut to = [from[0] + a, from[1] + b];
+                    bump_paddles(from, &mut to, GAP);
+                    let (left, right) = if from[0] <= from[1] { (0, 1) } else { (1, 0) };
+                    assert!(
+                        GAP - 1e-3 <= to[right] - to[left],
+                        "from {:?} by {} {} to {:?}",
+                        from,
+                        a,
+                        b,
+                        to
+                    );
+                }
+            }
+        }
+    }
+}
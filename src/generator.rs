@@ -0,0 +1,272 @@
+use {
+    crate::level::{BrickKind, Level, LevelBrick},
+    rand::{rngs::StdRng, Rng, SeedableRng},
+};
+
+/// The gap between generated bricks
+const SPACING: f32 = 8.0;
+
+/// How hard a generated wall is, growing with the wave number
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WaveParams {
+    pub rows: usize,
+    pub columns: usize,
+    /// the share of the cells holding a brick
+    pub density: f32,
+    /// the most hits a brick takes
+    pub max_hp: usize,
+    /// the share of the bricks being steel or bonus ones, two steel for a bonus
+    pub special_ratio: f32,
+}
+
+impl WaveParams {
+    /// The parameters of `wave`, counting from 1.
+    pub fn for_wave(wave: usize) -> Self {
+        let step = wave.max(1) - 1;
+        WaveParams {
+            rows: (4 + step / 2).min(8),
+            columns: (6 + step).min(13),
+            density: (0.5 + 0.05 * step as f32).min(0.9),
+            max_hp: (1 + step / 3).min(4),
+            special_ratio: (0.05 + 0.02 * step as f32).min(0.3),
+        }
+    }
+}
+
+/// The ways a wall can be shaped
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Shape {
+    /// random cells mirrored around the middle column
+    Symmetric,
+    /// blobs of bricks following a smooth noise
+    Noise,
+    /// steel corridors filled with bricks, opening to the bottom
+    Maze,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 3] = [Shape::Symmetric, Shape::Noise, Shape::Maze];
+}
+
+/// The wall of `wave` in the endless run started from `seed`, always the same for both.
+pub fn generate(seed: u64, wave: usize) -> Level {
+    let mut rng = StdRng::seed_from_u64(seed ^ (wave as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    let params = WaveParams::for_wave(wave);
+    // mazes need a few waves to get the hang of the steel
+    let shapes = if wave < 3 { 2 } else { Shape::ALL.len() };
+    let shape = Shape::ALL[rng.gen_range(0..shapes)];
+    let (rows, columns) = (params.rows, params.columns);
+    let mut cells = match shape {
+        Shape::Symmetric => symmetric(&mut rng, &params),
+        Shape::Noise => noise(&mut rng, &params),
+        Shape::Maze => maze(&mut rng, &params),
+    };
+    if !cells
+        .iter()
+        .flatten()
+        .any(|cell| cell.is_some_and(|k| k.is_breakable()))
+    {
+        let (row, column) = (rng.gen_range(0..rows), rng.gen_range(0..columns));
+        cells[row][column] = Some(BrickKind::Normal);
+    }
+    let mut bricks = Vec::new();
+    for (row, line) in cells.iter().enumerate() {
+        // a row shares its hit points, keeping the shapes readable
+        let row_hp = rng.gen_range(1..=params.max_hp);
+        for (column, cell) in line.iter().enumerate() {
+            let Some(kind) = *cell else { continue; };
+            let hp = match kind {
+                BrickKind::Normal => row_hp,
+                _ => 1,
+            };
+            bricks.push(LevelBrick {
+                kind,
+                hp,
+                ..LevelBrick::new(row, column)
+            });
+        }
+    }
+    Level {
+        name: format!("Wave {}", wave),
+        rows,
+        columns,
+        spacing: SPACING,
+        bricks,
+        layouts: Vec::new(),
+    }
+}
+
+/// A brick or none for a cell, made special at the rate of the wave.
+fn fill(rng: &mut StdRng, params: &WaveParams, filled: bool) -> Option<BrickKind> {
+    if !filled {
+        return None;
+    }
+    if !rng.gen_bool(params.special_ratio as f64) {
+        return Some(BrickKind::Normal);
+    }
+    Some(if rng.gen_ratio(2, 3) {
+        BrickKind::Steel
+    } else {
+        BrickKind::Bonus
+    })
+}
+
+fn symmetric(rng: &mut StdRng, params: &WaveParams) -> Vec<Vec<Option<BrickKind>>> {
+    let half = params.columns.div_ceil(2);
+    (0..params.rows)
+        .map(|_| {
+            let mut line: Vec<_> = (0..half)
+                .map(|_| {
+                    let filled = rng.gen_bool(params.density as f64);
+                    fill(rng, params, filled)
+                })
+                .collect();
+            let mirrored: Vec<_> = line
+                .iter()
+                .rev()
+                .skip(params.columns % 2)
+                .copied()
+                .collect();
+            line.extend(mirrored);
+            line
+        })
+        .collect()
+}
+
+/// Value noise on a coarse lattice, smoothly interpolated over the cells, filled where it is
+/// lowest.
+fn noise(rng: &mut StdRng, params: &WaveParams) -> Vec<Vec<Option<BrickKind>>> {
+    const CELLS_PER_KNOT: f32 = 3.0;
+    let knots_y = (params.rows as f32 / CELLS_PER_KNOT).ceil() as usize + 2;
+    let knots_x = (params.columns as f32 / CELLS_PER_KNOT).ceil() as usize + 2;
+    let knots: Vec<Vec<f32>> = (0..knots_y)
+        .map(|_| (0..knots_x).map(|_| rng.gen::<f32>()).collect())
+        .collect();
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let values: Vec<Vec<f32>> = (0..params.rows)
+        .map(|row| {
+            (0..params.columns)
+                .map(|column| {
+                    let y = row as f32 / CELLS_PER_KNOT;
+                    let x = column as f32 / CELLS_PER_KNOT;
+                    let (y0, x0) = (y as usize, x as usize);
+                    let (ty, tx) = (smooth(y.fract()), smooth(x.fract()));
+                    let top = knots[y0][x0] + (knots[y0][x0 + 1] - knots[y0][x0]) * tx;
+                    let bottom =
+                        knots[y0 + 1][x0] + (knots[y0 + 1][x0 + 1] - knots[y0 + 1][x0]) * tx;
+                    top + (bottom - top) * ty
+                })
+                .collect()
+        })
+        .collect();
+    // the threshold leaving the share of cells asked for by the density
+    let mut sorted: Vec<f32> = values.iter().flatten().copied().collect();
+    sorted.sort_by(f32::total_cmp);
+    let filled = (params.density * sorted.len() as f32) as usize;
+    let threshold = sorted.get(filled).copied().unwrap_or(f32::INFINITY);
+    values
+        .iter()
+        .map(|line| {
+            line.iter()
+                .map(|&value| fill(rng, params, value < threshold))
+                .collect()
+        })
+        .collect()
+}
+
+/// A maze carved with a random depth-first walk, its walls of steel and its corridors of
+/// bricks, open to the bottom at every corridor.
+fn maze(rng: &mut StdRng, params: &WaveParams) -> Vec<Vec<Option<BrickKind>>> {
+    let (rows, columns) = (params.rows, params.columns);
+    let mut open = vec![vec![false; columns]; rows];
+    // the corridors run through the odd cells, counting from the top
+    let mut stack = vec![(1.min(rows - 1), 1.min(columns - 1))];
+    open[stack[0].0][stack[0].1] = true;
+    while let Some(&(y, x)) = stack.last() {
+        let neighbours: Vec<(usize, usize)> = [(0, 2), (2, 0), (0, -2), (-2, 0)]
+            .iter()
+            .filter_map(|&(dy, dx): &(isize, isize)| {
+                let ny = y.checked_add_signed(dy)?;
+                let nx = x.checked_add_signed(dx)?;
+                (ny < rows && nx < columns && !open[ny][nx]).then_some((ny, nx))
+            })
+            .collect();
+        if neighbours.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let (ny, nx) = neighbours[rng.gen_range(0..neighbours.len())];
+        open[(y + ny) / 2][(x + nx) / 2] = true;
+        open[ny][nx] = true;
+        stack.push((ny, nx));
+    }
+    // break through the bottom wall below the corridors
+    if 1 < rows {
+        let above = open[rows - 2].clone();
+        for (cell, corridor) in open[rows - 1].iter_mut().zip(above) {
+            *cell |= corridor;
+        }
+    }
+    // rows count from the bottom in levels
+    open.iter()
+        .rev()
+        .map(|line| {
+            line.iter()
+                .map(|&corridor| {
+                    if !corridor {
+                        return Some(BrickKind::Steel);
+                    }
+                    let filled = rng.gen_bool(params.density as f64);
+                    match fill(rng, params, filled) {
+                        // the walls are steel enough
+                        Some(BrickKind::Steel) => Some(BrickKind::Normal),
+                        kind => kind,
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_wave() {
+        for wave in 1..=12 {
+            assert_eq!(generate(42, wave), generate(42, wave));
+        }
+        assert_ne!(generate(42, 5), generate(43, 5));
+    }
+
+    #[test]
+    fn waves_grow_harder_up_to_a_cap() {
+        let mut previous = WaveParams::for_wave(1);
+        assert_eq!(previous, WaveParams::for_wave(0));
+        for wave in 2..=40 {
+            let params = WaveParams::for_wave(wave);
+            assert!(previous.rows <= params.rows && params.rows <= 8);
+            assert!(previous.columns <= params.columns && params.columns <= 13);
+            assert!(previous.density <= params.density && params.density <= 0.9);
+            assert!(previous.max_hp <= params.max_hp && params.max_hp <= 4);
+            assert!(previous.special_ratio <= params.special_ratio);
+            previous = params;
+        }
+    }
+
+    #[test]
+    fn every_wave_can_be_cleared() {
+        for seed in 0..20 {
+            for wave in 1..=10 {
+                let level = generate(seed, wave);
+                let params = WaveParams::for_wave(wave);
+                assert!(0 < level.breakable_count(), "seed {} wave {}", seed, wave);
+                for brick in &level.bricks {
+                    assert!(brick.row < level.rows && brick.column < level.columns);
+                    assert!((1..=params.max_hp).contains(&brick.hp));
+                }
+            }
+        }
+    }
+}
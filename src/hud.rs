@@ -0,0 +1,289 @@
+use {
+    crate::{
+        arena::Arena,
+        state::AppState,
+        theme::ActiveTheme,
+        tween::{Ease, Lens, Tween, Tweens},
+        versus::Versus,
+    },
+    bevy::{
+        prelude::*,
+        window::{PrimaryWindow, WindowResized},
+    },
+};
+
+const HUD_FONT_SIZE: f32 = 32.0;
+const COMBO_FONT_SIZE: f32 = 56.0;
+
+#[derive(Resource)]
+pub struct Scoreboard {
+    pub score: usize,
+    pub remain_bricks: usize,
+    pub brick_in_row: usize,
+    pub keeping: bool,
+    pub lives: usize,
+    pub level: usize,
+}
+
+impl Default for Scoreboard {
+    fn default() -> Self {
+        Scoreboard {
+            score: 0,
+            remain_bricks: 0,
+            brick_in_row: 1,
+            keeping: false,
+            lives: 3,
+            level: 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Component, Debug, Eq, PartialEq)]
+pub enum HudWidget {
+    Score,
+    Lives,
+    Level,
+}
+
+/// The node overlaying the arena, which all widgets are anchored to
+#[derive(Component, Default)]
+pub struct HudFrame;
+
+#[derive(Component, Default)]
+pub struct TextBonus {
+    pub row: usize,
+}
+
+pub fn setup_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    arena: Res<Arena>,
+    theme: Res<ActiveTheme>,
+) {
+    let theme = &theme.0;
+    let label = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: HUD_FONT_SIZE,
+        color: theme.label,
+    };
+    let value = TextStyle {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: HUD_FONT_SIZE,
+        color: theme.value,
+    };
+    let widget = |name: &str| {
+        TextBundle::from_sections([
+            TextSection::new(name, label.clone()),
+            TextSection::from_style(value.clone()),
+        ])
+    };
+    let size = arena.visible_size();
+    // the root centers the frame, which is scaled to cover the arena exactly
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::all(Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_children(|root| {
+            root.spawn(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(size.x), Val::Px(size.y)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(arena.wall_thickness)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(HudFrame)
+            .with_children(|frame| {
+                frame
+                    .spawn(NodeBundle {
+                        style: Style {
+                            size: Size::width(Val::Percent(100.0)),
+                            justify_content: JustifyContent::SpaceBetween,
+                            padding: UiRect::horizontal(Val::Px(10.0)),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .with_children(|bar| {
+                        bar.spawn(widget("Score: ")).insert(HudWidget::Score);
+                        bar.spawn(widget("Level: ")).insert(HudWidget::Level);
+                        bar.spawn(widget("Lives: ")).insert(HudWidget::Lives);
+                    });
+                // combo counter
+                frame
+                    .spawn(
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: COMBO_FONT_SIZE,
+                                color: theme.accent.with_a(0.8),
+                            },
+                        )
+                        .with_text_alignment(TextAlignment::Center)
+                        .with_style(Style {
+                            margin: UiRect::top(Val::Px(10.0)),
+                            ..Default::default()
+                        }),
+                    )
+                    .insert(TextBonus::default());
+            });
+        });
+}
+
+/// Scale the UI so that the frame keeps covering the arena at any window size.
+///
+/// Since the UI works in logical pixels, the DPI of the display is taken into account by Bevy.
+pub fn hud_layout_system(
+    arena: Res<Arena>,
+    mut resized: EventReader<WindowResized>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+    mut frames: Query<&mut Style, With<HudFrame>>,
+) {
+    let resized = resized.iter().last().is_some();
+    if !resized && !arena.is_changed() {
+        return;
+    }
+    let size = arena.visible_size();
+    if let Ok(window) = windows.get_single() {
+        let scale = (window.width() / size.x).min(window.height() / size.y);
+        if 0.0 < scale {
+            ui_scale.scale = scale as f64;
+        }
+    }
+    for mut style in frames.iter_mut() {
+        style.size = Size::new(Val::Px(size.x), Val::Px(size.y));
+        style.padding = UiRect::all(Val::Px(arena.wall_thickness));
+    }
+}
+
+/// Recolor the widgets when the theme changes.
+pub fn hud_theme_system(theme: Res<ActiveTheme>, mut query: Query<&mut Text, With<HudWidget>>) {
+    if !theme.is_changed() {
+        return;
+    }
+    for mut text in query.iter_mut() {
+        text.sections[0].style.color = theme.0.label;
+        text.sections[1].style.color = theme.0.value;
+    }
+}
+
+/// Hide the widgets on the title screen, where the computer is playing, and in the editor.
+pub fn hud_visibility_system(
+    state: Res<State<AppState>>,
+    mut frames: Query<&mut Visibility, With<HudFrame>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for mut visibility in frames.iter_mut() {
+        *visibility = if matches!(state.0, AppState::Title | AppState::Editor) {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
+
+pub fn scoreboard_system(
+    mut commands: Commands,
+    scoreboard: Res<Scoreboard>,
+    versus: Res<Versus>,
+    mut shown_score: Local<Option<usize>>,
+    mut query: Query<(Entity, &HudWidget, &mut Text)>,
+) {
+    if !scoreboard.is_changed() && !versus.is_changed() {
+        return;
+    }
+    let score = if versus.active {
+        versus.scores.iter().sum()
+    } else {
+        scoreboard.score
+    };
+    let score_changed = shown_score.is_some_and(|shown| shown != score);
+    *shown_score = Some(score);
+    for (entity, widget, mut text) in query.iter_mut() {
+        // both players side by side in versus, the first one on the left
+        let value = match widget {
+            HudWidget::Score if versus.active => {
+                format!("{} : {}", versus.scores[0], versus.scores[1])
+            }
+            HudWidget::Lives if versus.active => {
+                format!("{} : {}", versus.lives[0], versus.lives[1])
+            }
+            HudWidget::Score => format!("{}", scoreboard.score),
+            HudWidget::Level => format!("{}", scoreboard.level),
+            HudWidget::Lives => format!("{}", scoreboard.lives),
+        };
+        if text.sections[1].value != value {
+            text.sections[1].value = value;
+        }
+        if *widget != HudWidget::Score || !score_changed {
+            continue;
+        }
+        // the score pulses when it changes, and keeps highlighted after the last brick
+        let tween = if 0 == scoreboard.remain_bricks {
+            Tween::new(
+                Lens::TextSize {
+                    start: HUD_FONT_SIZE,
+                    end: 1.3 * HUD_FONT_SIZE,
+                },
+                Ease::ElasticOut,
+                1.0,
+            )
+        } else {
+            Tween::new(
+                Lens::TextSize {
+                    start: 1.3 * HUD_FONT_SIZE,
+                    end: HUD_FONT_SIZE,
+                },
+                Ease::QuadOut,
+                0.4,
+            )
+        };
+        commands.entity(entity).insert(Tweens::new(tween));
+    }
+}
+
+pub fn bonus_notifier_system(
+    mut commands: Commands,
+    theme: Res<ActiveTheme>,
+    mut bonus_query: Query<(Entity, &mut Text, &TextBonus), Changed<TextBonus>>,
+) {
+    for (entity, mut text, bonus) in bonus_query.iter_mut() {
+        if bonus.row < 2 {
+            continue;
+        }
+        text.sections[0].value = format!("combo x{}", bonus.row);
+        // grow within the frame instead of scaling the node, which would misalign it
+        let color = theme.0.accent;
+        commands.entity(entity).insert(
+            Tweens::new(Tween::new(
+                Lens::TextColor {
+                    start: color,
+                    end: color.with_a(0.0),
+                },
+                Ease::QuadIn,
+                0.5,
+            ))
+            .with(Tween::new(
+                Lens::TextSize {
+                    start: COMBO_FONT_SIZE,
+                    end: 1.5 * COMBO_FONT_SIZE,
+                },
+                Ease::CubicOut,
+                0.5,
+            )),
+        );
+    }
+}
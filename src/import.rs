@@ -0,0 +1,291 @@
+use {
+    crate::level::{BrickKind, Level, LevelBrick},
+    bevy::{
+        prelude::*,
+        render::{
+            render_resource::TextureFormat,
+            texture::{CompressedImageFormats, ImageType, TextureError},
+        },
+    },
+    std::{error, fmt, fs, path::Path},
+};
+
+/// How the pixels of an image become bricks
+#[derive(Clone, Debug)]
+pub struct ImportOptions {
+    /// pixels of exactly these colors become bricks of the kind instead of normal ones
+    pub palette: Vec<([u8; 3], BrickKind)>,
+    /// pixels with less alpha leave their cell empty
+    pub alpha_threshold: u8,
+    /// bigger images are sampled down to fit, keeping their aspect
+    pub max_columns: usize,
+    pub max_rows: usize,
+    pub spacing: f32,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        ImportOptions {
+            palette: vec![
+                ([128, 128, 128], BrickKind::Steel),
+                ([255, 215, 0], BrickKind::Bonus),
+            ],
+            alpha_threshold: 128,
+            max_columns: 32,
+            max_rows: 16,
+            spacing: 2.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Decode(TextureError),
+    /// only images of 8 bits per channel are read
+    Format(TextureFormat),
+    /// the image gives no brick the ball can break, so the level could never be cleared
+    NoBreakable,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Decode(e) => write!(f, "failed to decode the image: {}", e),
+            ImportError::Format(format) => {
+                write!(
+                    f,
+                    "unsupported pixel format {:?}, 8 bits per channel expected",
+                    format
+                )
+            }
+            ImportError::NoBreakable => write!(
+                f,
+                "no breakable brick in the image, the level could never be cleared"
+            ),
+        }
+    }
+}
+
+impl error::Error for ImportError {}
+
+/// Turn a PNG into a level named `name`, a brick for each opaque pixel with its color.
+pub fn level_from_png(
+    name: &str,
+    bytes: &[u8],
+    options: &ImportOptions,
+) -> Result<Level, ImportError> {
+    let image = Image::from_buffer(
+        bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+    )
+    .map_err(ImportError::Decode)?;
+    level_from_image(name, &image, options)
+}
+
+/// Turn a decoded image into a level named `name`, see [`level_from_png`].
+pub fn level_from_image(
+    name: &str,
+    image: &Image,
+    options: &ImportOptions,
+) -> Result<Level, ImportError> {
+    let format = image.texture_descriptor.format;
+    if !matches!(
+        format,
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+    ) {
+        return Err(ImportError::Format(format));
+    }
+    let size = image.texture_descriptor.size;
+    let (width, height) = (size.width as usize, size.height as usize);
+    // sample every `step` pixels, from the middle of each block
+    let step = width
+        .div_ceil(options.max_columns.max(1))
+        .max(height.div_ceil(options.max_rows.max(1)))
+        .max(1);
+    // a partial block at the right or the bottom still makes a column or a row
+    let (columns, rows) = (width.div_ceil(step), height.div_ceil(step));
+    let mut bricks = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            // images go down from the top, levels up from the bottom
+            let x = (column * step + step / 2).min(width - 1);
+            let y = ((rows - 1 - row) * step + step / 2).min(height - 1);
+            let i = 4 * (y * width + x);
+            let [r, g, b, a] = [0, 1, 2, 3].map(|c| image.data[i + c]);
+            if a < options.alpha_threshold {
+                continue;
+            }
+            let kind = options
+                .palette
+                .iter()
+                .find(|(color, _)| *color == [r, g, b])
+                .map_or(BrickKind::Normal, |&(_, kind)| kind);
+            bricks.push(LevelBrick {
+                // the special kinds take the colors of the theme
+                color: (kind == BrickKind::Normal).then(|| Color::rgb_u8(r, g, b)),
+                kind,
+                ..LevelBrick::new(row, column)
+            });
+        }
+    }
+    let level = Level {
+        name: name.to_string(),
+        rows,
+        columns,
+        spacing: options.spacing,
+        bricks,
+        layouts: Vec::new(),
+    };
+    if 0 == level.breakable_count() {
+        return Err(ImportError::NoBreakable);
+    }
+    Ok(level)
+}
+
+fn parse_color(hex: &str) -> Result<[u8; 3], String> {
+    let hex = hex.trim_start_matches('#');
+    let value = u32::from_str_radix(hex, 16)
+        .ok()
+        .filter(|_| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| format!("`{}` isn't a RRGGBB color", hex))?;
+    Ok([(value >> 16) as u8, (value >> 8) as u8, value as u8])
+}
+
+/// The `import` subcommand: `import <image.png> [<out.level.ron>] [--steel RRGGBB]...
+/// [--bonus RRGGBB]...`, writing the level to the standard output without a file.
+pub fn import_command(args: &[String]) -> Result<(), String> {
+    let usage = "usage: breakout import <image.png> [<out.level.ron>] [--steel RRGGBB]... \
+                 [--bonus RRGGBB]...";
+    let mut options = ImportOptions::default();
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let kind = match arg.as_str() {
+            "--steel" => BrickKind::Steel,
+            "--bonus" => BrickKind::Bonus,
+            _ => {
+                paths.push(arg);
+                continue;
+            }
+        };
+        let color = args.next().ok_or(usage)?;
+        options.palette.push((parse_color(color)?, kind));
+    }
+    let (input, output) = match paths[..] {
+        [input] => (input, None),
+        [input, output] => (input, Some(output)),
+        _ => return Err(usage.to_string()),
+    };
+    let bytes = fs::read(input).map_err(|e| format!("failed to read {}: {}", input, e))?;
+    let name = Path::new(input)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let level = level_from_png(&name, &bytes, &options).map_err(|e| e.to_string())?;
+    match output {
+        Some(output) => level
+            .save(Path::new(output))
+            .map_err(|e| format!("failed to write {}: {}", output, e)),
+        None => {
+            let ron = level.to_ron().map_err(|e| e.to_string())?;
+            println!("{}", ron);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        bevy::render::render_resource::{Extent3d, TextureDimension},
+    };
+
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const STEEL: [u8; 4] = [128, 128, 128, 255];
+    const GOLD: [u8; 4] = [255, 215, 0, 255];
+
+    /// An RGBA image of `width` pixels a row, from the top.
+    fn image(width: u32, pixels: &[[u8; 4]]) -> Image {
+        let size = Extent3d {
+            width,
+            height: pixels.len() as u32 / width,
+            depth_or_array_layers: 1,
+        };
+        Image::new(
+            size,
+            TextureDimension::D2,
+            pixels.concat(),
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    fn cells(level: &Level) -> Vec<(usize, usize, BrickKind)> {
+        let mut cells: Vec<_> = level
+            .bricks
+            .iter()
+            .map(|b| (b.row, b.column, b.kind))
+            .collect();
+        cells.sort_by_key(|&(row, column, _)| (row, column));
+        cells
+    }
+
+    #[test]
+    fn parse_color_reads_hex_with_or_without_hash() {
+        assert_eq!(parse_color("#ff8000"), Ok([255, 128, 0]));
+        assert_eq!(parse_color("FF8000"), Ok([255, 128, 0]));
+        assert_eq!(parse_color("0a0B0c"), Ok([10, 11, 12]));
+        for bad in ["", "#", "ff800", "ff80000", "gg0000", "+ff800", "#ff 800"] {
+            assert!(parse_color(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn pixels_become_bricks_of_their_kind() {
+        // the top row of the image is the top row of the level
+        let image = image(3, &[RED, CLEAR, STEEL, GOLD, RED, CLEAR]);
+        let level = level_from_image("test", &image, &ImportOptions::default()).unwrap();
+        assert_eq!((level.columns, level.rows), (3, 2));
+        assert_eq!(
+            cells(&level),
+            [
+                (0, 0, BrickKind::Bonus),
+                (0, 1, BrickKind::Normal),
+                (1, 0, BrickKind::Normal),
+                (1, 2, BrickKind::Steel),
+            ]
+        );
+        let red = level.bricks.iter().find(|b| (b.row, b.column) == (1, 0));
+        assert_eq!(red.unwrap().color, Some(Color::rgb_u8(255, 0, 0)));
+        // the special kinds take the colors of the theme
+        let gold = level.bricks.iter().find(|b| (b.row, b.column) == (0, 0));
+        assert_eq!(gold.unwrap().color, None);
+    }
+
+    #[test]
+    fn big_images_are_sampled_down_to_fit() {
+        let options = ImportOptions::default();
+        // 40 pixels sampled every 2 in a single row, which must not be lost
+        let strip = level_from_image("strip", &image(40, &[RED; 40]), &options).unwrap();
+        assert_eq!((strip.columns, strip.rows), (20, 1));
+        assert_eq!(strip.bricks.len(), 20);
+        // a partial block at the edge still makes a column
+        let odd = level_from_image("odd", &image(33, &[RED; 33]), &options).unwrap();
+        assert_eq!((odd.columns, odd.rows), (17, 1));
+        assert!(odd.columns <= options.max_columns);
+    }
+
+    #[test]
+    fn images_without_breakable_bricks_are_refused() {
+        let options = ImportOptions::default();
+        let clear = level_from_image("clear", &image(2, &[CLEAR; 4]), &options);
+        assert!(matches!(clear, Err(ImportError::NoBreakable)));
+        let steel = level_from_image("steel", &image(2, &[STEEL; 4]), &options);
+        assert!(matches!(steel, Err(ImportError::NoBreakable)));
+        let garbage = level_from_png("garbage", b"not a png", &options);
+        assert!(matches!(garbage, Err(ImportError::Decode(_))));
+    }
+}
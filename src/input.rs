@@ -0,0 +1,303 @@
+use {
+    crate::{arena::ArenaCamera, settings::Settings},
+    bevy::{prelude::*, utils::HashSet},
+    serde::{Deserialize, Serialize},
+    std::{collections::BTreeMap, fmt},
+};
+
+/// What the player wants to do, independent of the device
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum InputAction {
+    MoveLeft,
+    MoveRight,
+    /// the paddle of the second player in versus and co-op
+    SecondLeft,
+    SecondRight,
+    Launch,
+    Pause,
+    MenuUp,
+    MenuDown,
+    Confirm,
+    Back,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 10] = [
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::SecondLeft,
+        InputAction::SecondRight,
+        InputAction::Launch,
+        InputAction::Pause,
+        InputAction::MenuUp,
+        InputAction::MenuDown,
+        InputAction::Confirm,
+        InputAction::Back,
+    ];
+    pub fn name(self) -> &'static str {
+        match self {
+            InputAction::MoveLeft => "Move left",
+            InputAction::MoveRight => "Move right",
+            InputAction::SecondLeft => "Player 2 left",
+            InputAction::SecondRight => "Player 2 right",
+            InputAction::Launch => "Launch",
+            InputAction::Pause => "Pause",
+            InputAction::MenuUp => "Menu up",
+            InputAction::MenuDown => "Menu down",
+            InputAction::Confirm => "Confirm",
+            InputAction::Back => "Back",
+        }
+    }
+    fn is_menu(self) -> bool {
+        matches!(
+            self,
+            InputAction::MenuUp | InputAction::MenuDown | InputAction::Confirm | InputAction::Back
+        )
+    }
+    /// Whether both actions are read in the same situation, so that they can't share a binding.
+    fn overlaps(self, other: InputAction) -> bool {
+        self.is_menu() == other.is_menu()
+            || self == InputAction::Pause
+            || other == InputAction::Pause
+    }
+}
+
+/// A physical input which can trigger an [`InputAction`]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// a button on any connected gamepad
+    Gamepad(GamepadButtonType),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{:?}", key),
+            Binding::Mouse(button) => write!(f, "Mouse {:?}", button),
+            Binding::Gamepad(button) => write!(f, "Pad {:?}", button),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct InputBindings {
+    pub actions: BTreeMap<InputAction, Vec<Binding>>,
+    /// let the paddle follow the mouse cursor
+    pub mouse_control: bool,
+    /// ignore gamepad stick deflections smaller than this
+    pub stick_dead_zone: f32,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        let actions = BTreeMap::from([
+            (
+                InputAction::MoveLeft,
+                vec![
+                    Binding::Key(KeyCode::Left),
+                    Binding::Key(KeyCode::A),
+                    Binding::Gamepad(GamepadButtonType::DPadLeft),
+                ],
+            ),
+            (
+                InputAction::MoveRight,
+                vec![
+                    Binding::Key(KeyCode::Right),
+                    Binding::Key(KeyCode::D),
+                    Binding::Gamepad(GamepadButtonType::DPadRight),
+                ],
+            ),
+            (
+                InputAction::SecondLeft,
+                vec![Binding::Key(KeyCode::J), Binding::Key(KeyCode::Numpad4)],
+            ),
+            (
+                InputAction::SecondRight,
+                vec![Binding::Key(KeyCode::L), Binding::Key(KeyCode::Numpad6)],
+            ),
+            (
+                InputAction::Launch,
+                vec![
+                    Binding::Key(KeyCode::Space),
+                    Binding::Mouse(MouseButton::Left),
+                    Binding::Gamepad(GamepadButtonType::South),
+                ],
+            ),
+            (
+                InputAction::Pause,
+                vec![
+                    Binding::Key(KeyCode::Escape),
+                    Binding::Key(KeyCode::P),
+                    Binding::Gamepad(GamepadButtonType::Start),
+                ],
+            ),
+            (
+                InputAction::MenuUp,
+                vec![
+                    Binding::Key(KeyCode::Up),
+                    Binding::Key(KeyCode::W),
+                    Binding::Gamepad(GamepadButtonType::DPadUp),
+                ],
+            ),
+            (
+                InputAction::MenuDown,
+                vec![
+                    Binding::Key(KeyCode::Down),
+                    Binding::Key(KeyCode::S),
+                    Binding::Gamepad(GamepadButtonType::DPadDown),
+                ],
+            ),
+            (
+                InputAction::Confirm,
+                vec![
+                    Binding::Key(KeyCode::Return),
+                    Binding::Key(KeyCode::Space),
+                    Binding::Gamepad(GamepadButtonType::South),
+                ],
+            ),
+            (
+                InputAction::Back,
+                vec![
+                    Binding::Key(KeyCode::Back),
+                    Binding::Gamepad(GamepadButtonType::East),
+                ],
+            ),
+        ]);
+        InputBindings {
+            actions,
+            mouse_control: true,
+            stick_dead_zone: 0.15,
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn bindings(&self, action: InputAction) -> &[Binding] {
+        self.actions.get(&action).map_or(&[], |v| v.as_slice())
+    }
+    /// Give the default bindings to the actions missing from an older settings file.
+    pub fn complete(&mut self) {
+        for (action, bindings) in InputBindings::default().actions {
+            self.actions.entry(action).or_insert(bindings);
+        }
+    }
+    /// Make `binding` the primary binding of `action`, taking it away from the actions read
+    /// in the same situation.
+    pub fn rebind(&mut self, action: InputAction, binding: Binding) {
+        for (other, bindings) in self.actions.iter_mut() {
+            if action.overlaps(*other) {
+                bindings.retain(|b| *b != binding);
+            }
+        }
+        self.actions.entry(action).or_default().insert(0, binding);
+    }
+}
+
+/// The state of every [`InputAction`] in this frame
+#[derive(Debug, Default, Resource)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+    /// horizontal movement request in `-1.0..=1.0` from keys, buttons or sticks
+    pub axis: f32,
+    /// the x coordinate in world space the paddle should follow, while the mouse is in charge
+    pub pointer_x: Option<f32>,
+    /// horizontal movement request of the second player in versus and co-op, from keys only
+    pub second_axis: f32,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+    /// Press `action` for this frame, for players without a device like the autopilot.
+    pub fn press(&mut self, action: InputAction) {
+        self.pressed.insert(action);
+        self.just_pressed.insert(action);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn action_state_system(
+    settings: Res<Settings>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut cursor_moved: EventReader<CursorMoved>,
+    cameras: Query<(&Camera, &GlobalTransform), With<ArenaCamera>>,
+    mut state: ResMut<ActionState>,
+) {
+    let bindings = &settings.bindings;
+    let check = |binding: &Binding, just: bool| match *binding {
+        Binding::Key(key) if just => keyboard.just_pressed(key),
+        Binding::Key(key) => keyboard.pressed(key),
+        Binding::Mouse(button) if just => mouse.just_pressed(button),
+        Binding::Mouse(button) => mouse.pressed(button),
+        Binding::Gamepad(button_type) => gamepads.iter().any(|gamepad| {
+            let button = GamepadButton::new(gamepad, button_type);
+            if just {
+                gamepad_buttons.just_pressed(button)
+            } else {
+                gamepad_buttons.pressed(button)
+            }
+        }),
+    };
+    let state = &mut *state;
+    state.pressed.clear();
+    state.just_pressed.clear();
+    for (action, list) in bindings.actions.iter() {
+        if list.iter().any(|b| check(b, false)) {
+            state.pressed.insert(*action);
+        }
+        if list.iter().any(|b| check(b, true)) {
+            state.just_pressed.insert(*action);
+        }
+    }
+
+    let mut axis = 0.0;
+    if state.pressed(InputAction::MoveLeft) {
+        axis -= 1.0;
+    }
+    if state.pressed(InputAction::MoveRight) {
+        axis += 1.0;
+    }
+    for gamepad in gamepads.iter() {
+        let stick = gamepad_axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or_default();
+        if bindings.stick_dead_zone < stick.abs() {
+            axis += stick;
+        }
+    }
+    state.axis = axis.clamp(-1.0, 1.0);
+    state.second_axis = match (
+        state.pressed(InputAction::SecondLeft),
+        state.pressed(InputAction::SecondRight),
+    ) {
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        _ => 0.0,
+    };
+
+    // the last device touched takes over the paddle
+    let moved = cursor_moved.iter().last();
+    if state.axis != 0.0 || !bindings.mouse_control {
+        state.pointer_x = None;
+        return;
+    }
+    let Some(moved) = moved else { return; };
+    for (camera, transform) in cameras.iter() {
+        if let Some(pos) = camera.viewport_to_world_2d(transform, moved.position) {
+            state.pointer_x = Some(pos.x);
+            break;
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use {
+    crate::level::{is_default, is_one, one, BrickKind, LevelBrick},
+    bevy::prelude::*,
+    serde::{Deserialize, Serialize},
+    std::f32::consts::TAU,
+};
+
+/// A brick placed by a generator, around the middle of its layout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spot {
+    /// the row or ring it belongs to, for the colors of the theme
+    pub band: usize,
+    pub center: Vec2,
+}
+
+/// The arrangements of bricks beyond the grid of a level
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Shape {
+    /// rows of `columns` bricks, every other one shifted by half a brick
+    Hex { rows: usize, columns: usize },
+    /// rings around a middle one of `bricks` bricks, each as full as its length allows
+    Rings { rings: usize, bricks: usize },
+    /// `bricks` bricks winding out from the middle
+    Spiral { bricks: usize },
+    /// rows shrinking by a brick from a `base` row, each one centered on the one below
+    Pyramid { base: usize },
+    /// rows growing by a brick up to a middle row of `width` bricks, then shrinking again
+    Diamond { width: usize },
+}
+
+impl Shape {
+    /// The bricks of the shape, for bricks of `brick` size and `spacing` gaps between them.
+    pub fn spots(self, brick: Vec2, spacing: f32) -> Vec<Spot> {
+        let pitch = brick + Vec2::splat(spacing);
+        match self {
+            Shape::Hex { rows, columns } => hex(rows, columns, pitch),
+            Shape::Rings {
+                rings: count,
+                bricks,
+            } => rings(count, bricks, pitch),
+            Shape::Spiral { bricks } => spiral(bricks, pitch),
+            Shape::Pyramid { base } => pyramid(base, pitch),
+            Shape::Diamond { width } => diamond(width, pitch),
+        }
+    }
+}
+
+fn default_brick() -> Vec2 {
+    Vec2::new(40.0, 20.0)
+}
+
+fn default_spacing() -> f32 {
+    4.0
+}
+
+/// Bricks of a [`crate::level::Level`] laid out by a generator instead of the grid
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Layout {
+    pub shape: Shape,
+    /// from the middle of the brick area to the middle of the layout
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub offset: Vec2,
+    #[serde(default = "default_brick")]
+    pub brick: Vec2,
+    #[serde(default = "default_spacing")]
+    pub spacing: f32,
+    /// the color, kind and hit points of all the bricks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub kind: BrickKind,
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub hp: usize,
+}
+
+impl Layout {
+    pub fn new(shape: Shape) -> Self {
+        Layout {
+            shape,
+            offset: Vec2::ZERO,
+            brick: default_brick(),
+            spacing: default_spacing(),
+            color: None,
+            kind: BrickKind::Normal,
+            hp: 1,
+        }
+    }
+    /// The bricks, their bands as rows, and their centers from the middle of the brick area.
+    pub fn bricks(&self) -> Vec<(LevelBrick, Vec2)> {
+        let spots = self.shape.spots(self.brick, self.spacing);
+        // center the bounding box on the offset
+        let (min, max) = spots.iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), spot| (min.min(spot.center), max.max(spot.center)),
+        );
+        let middle = (min + max) / 2.0;
+        spots
+            .iter()
+            .enumerate()
+            .map(|(index, spot)| {
+                let brick = LevelBrick {
+                    color: self.color,
+                    kind: self.kind,
+                    hp: self.hp,
+                    ..LevelBrick::new(spot.band, index)
+                };
+                (brick, spot.center - middle + self.offset)
+            })
+            .collect()
+    }
+    /// The bands of the bricks, the rows of the theme gradient.
+    pub fn bands(&self) -> usize {
+        let spots = self.shape.spots(self.brick, self.spacing);
+        spots.iter().map(|spot| spot.band + 1).max().unwrap_or(1)
+    }
+    pub fn breakable_count(&self) -> usize {
+        if !self.kind.is_breakable() {
+            return 0;
+        }
+        self.shape.spots(self.brick, self.spacing).len()
+    }
+}
+
+/// Rows laid on top of each other from the bottom, each of a count of bricks centered on
+/// `shift` bricks from the left.
+fn stack(counts: impl Iterator<Item = (usize, f32)>, pitch: Vec2) -> Vec<Spot> {
+    counts
+        .enumerate()
+        .flat_map(|(row, (count, shift))| {
+            (0..count).map(move |column| Spot {
+                band: row,
+                center: Vec2::new((column as f32 + shift) * pitch.x, row as f32 * pitch.y),
+            })
+        })
+        .collect()
+}
+
+pub fn hex(rows: usize, columns: usize, pitch: Vec2) -> Vec<Spot> {
+    stack(
+        (0..rows).map(|row| (columns, if row % 2 == 1 { 0.5 } else { 0.0 })),
+        pitch,
+    )
+}
+
+pub fn pyramid(base: usize, pitch: Vec2) -> Vec<Spot> {
+    stack((0..base).map(|row| (base - row, row as f32 / 2.0)), pitch)
+}
+
+pub fn diamond(width: usize, pitch: Vec2) -> Vec<Spot> {
+    let middle = width.max(1) - 1;
+    stack(
+        (0..2 * middle + 1).map(|row| {
+            let narrower = row.abs_diff(middle);
+            (width - narrower, narrower as f32 / 2.0)
+        }),
+        pitch,
+    )
+}
+
+/// The centers of bricks on a circle of `radius`, `count` of them evenly spread from the top.
+fn circle(radius: f32, count: usize) -> impl Iterator<Item = Vec2> {
+    (0..count).map(move |index| {
+        let angle = TAU * index as f32 / count as f32;
+        radius * Vec2::new(angle.sin(), angle.cos())
+    })
+}
+
+pub fn rings(count: usize, bricks: usize, pitch: Vec2) -> Vec<Spot> {
+    // bricks aren't turned along the rings, so they need their length between rings too
+    let step = pitch.max_element();
+    let first = bricks.max(1) as f32 * pitch.x / TAU;
+    (0..count)
+        .flat_map(|ring| {
+            let radius = first + ring as f32 * step;
+            let fitting = if ring == 0 {
+                bricks
+            } else {
+                (TAU * radius / pitch.x) as usize
+            };
+            // the outer rings come first, like the bottom rows of a grid
+            circle(radius, fitting).map(move |center| Spot {
+                band: count - 1 - ring,
+                center,
+            })
+        })
+        .collect()
+}
+
+pub fn spiral(bricks: usize, pitch: Vec2) -> Vec<Spot> {
+    // an archimedean spiral, its turns a ring apart, starting a turn out
+    let step = pitch.max_element();
+    let mut angle = TAU;
+    let mut spots = Vec::with_capacity(bricks);
+    for _ in 0..bricks {
+        let radius = step * angle / TAU;
+        spots.push(Spot {
+            band: (angle / TAU) as usize - 1,
+            center: radius * Vec2::new(angle.sin(), angle.cos()),
+        });
+        // the next brick a brick further along the curve
+        angle += pitch.x / radius;
+    }
+    // the outer turns come first, like the outer rings
+    let last = spots.last().map_or(0, |spot| spot.band);
+    for spot in spots.iter_mut() {
+        spot.band = last - spot.band;
+    }
+    spots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PITCH: Vec2 = Vec2::new(44.0, 24.0);
+
+    fn count_bands(spots: &[Spot]) -> Vec<usize> {
+        let bands = spots.iter().map(|spot| spot.band + 1).max().unwrap_or(0);
+        (0..bands)
+            .map(|band| spots.iter().filter(|spot| spot.band == band).count())
+            .collect()
+    }
+
+    /// Whether no two spots are closer than a brick is high.
+    fn apart(spots: &[Spot]) -> bool {
+        spots.iter().enumerate().all(|(i, a)| {
+            spots[i + 1..]
+                .iter()
+                .all(|b| PITCH.y <= a.center.distance(b.center) + 1e-3)
+        })
+    }
+
+    #[test]
+    fn hex_shifts_every_other_row() {
+        let spots = hex(3, 5, PITCH);
+        assert_eq!(count_bands(&spots), [5, 5, 5]);
+        assert_eq!(spots[0].center.x, 0.0);
+        assert_eq!(spots[5].center.x, PITCH.x / 2.0);
+        assert!(apart(&spots));
+    }
+
+    #[test]
+    fn pyramid_shrinks_to_a_brick() {
+        let spots = pyramid(4, PITCH);
+        assert_eq!(count_bands(&spots), [4, 3, 2, 1]);
+        assert_eq!(spots[9].center.x, 1.5 * PITCH.x);
+        assert!(apart(&spots));
+    }
+
+    #[test]
+    fn diamond_grows_then_shrinks() {
+        let spots = diamond(4, PITCH);
+        assert_eq!(count_bands(&spots), [1, 2, 3, 4, 3, 2, 1]);
+        assert!(apart(&spots));
+        assert!(diamond(0, PITCH).is_empty());
+    }
+
+    #[test]
+    fn rings_fill_outward() {
+        let spots = rings(3, 6, PITCH);
+        let counts = count_bands(&spots);
+        // the innermost ring is the last band
+        assert_eq!(counts[2], 6);
+        assert!(counts[0] >= counts[1] && counts[1] >= counts[2]);
+        assert!(apart(&spots));
+    }
+
+    #[test]
+    fn spiral_winds_out_from_the_middle() {
+        let spots = spiral(30, PITCH);
+        assert_eq!(spots.len(), 30);
+        assert!(spots.windows(2).all(|pair| pair[0].band >= pair[1].band));
+        assert_eq!(spots.last().unwrap().band, 0);
+        assert!(apart(&spots));
+    }
+}
@@ -0,0 +1,235 @@
+use {
+    crate::{layout::Layout, theme::Theme},
+    bevy::{
+        asset::{AssetLoader, Error, LoadContext, LoadState, LoadedAsset},
+        prelude::*,
+        reflect::TypeUuid,
+        utils::BoxedFuture,
+    },
+    serde::{Deserialize, Serialize},
+    std::{fs, io, path::Path},
+};
+
+/// The levels of the campaign, played in the order of their file names
+pub const LEVEL_FOLDER: &str = "levels";
+
+/// How much darker a brick gets for each hit point beyond the first
+const HP_SHADE: f32 = 0.15;
+
+/// What a brick does when the ball hits it
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BrickKind {
+    #[default]
+    Normal,
+    /// never breaks, and doesn't need to for the level to be cleared
+    Steel,
+    /// gives an extra life when broken
+    Bonus,
+}
+
+impl BrickKind {
+    pub const ALL: [BrickKind; 3] = [BrickKind::Normal, BrickKind::Steel, BrickKind::Bonus];
+    pub fn name(self) -> &'static str {
+        match self {
+            BrickKind::Normal => "normal",
+            BrickKind::Steel => "steel",
+            BrickKind::Bonus => "bonus",
+        }
+    }
+    pub fn is_breakable(self) -> bool {
+        self != BrickKind::Steel
+    }
+}
+
+pub(crate) fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+pub(crate) fn one() -> usize {
+    1
+}
+
+pub(crate) fn is_one(value: &usize) -> bool {
+    *value == 1
+}
+
+/// A brick of a [`Level`], in a cell of its grid
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LevelBrick {
+    /// counting from the bottom
+    pub row: usize,
+    pub column: usize,
+    /// overrides the color the theme gives to the row
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub kind: BrickKind,
+    /// the hits it takes to break
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub hp: usize,
+}
+
+impl LevelBrick {
+    pub fn new(row: usize, column: usize) -> Self {
+        LevelBrick {
+            row,
+            column,
+            color: None,
+            kind: BrickKind::Normal,
+            hp: 1,
+        }
+    }
+    /// The color of the brick in a level of `rows` rows, darker with more hit points left.
+    pub fn color(&self, theme: &Theme, rows: usize) -> Color {
+        let color = match self.kind {
+            BrickKind::Normal => self.color.unwrap_or_else(|| theme.brick(self.row, rows)),
+            BrickKind::Steel => theme.walls,
+            BrickKind::Bonus => theme.accent,
+        };
+        let shade = (1.0 - HP_SHADE * self.hp.saturating_sub(1) as f32).max(0.4);
+        Color::rgba(
+            color.r() * shade,
+            color.g() * shade,
+            color.b() * shade,
+            color.a(),
+        )
+    }
+}
+
+/// A wall of bricks, loaded from `*.level.ron` files
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, TypeUuid)]
+#[uuid = "3b9d6c42-7a1e-4f58-8c0d-2e6f9a4b1d73"]
+pub struct Level {
+    pub name: String,
+    /// the grid filling the brick area of the arena
+    pub rows: usize,
+    pub columns: usize,
+    /// the gap between neighbouring cells
+    pub spacing: f32,
+    pub bricks: Vec<LevelBrick>,
+    /// bricks laid out in shapes other than the grid, see [`crate::layout`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layouts: Vec<Layout>,
+}
+
+impl Default for Level {
+    /// The full wall of the original game.
+    fn default() -> Self {
+        let (rows, columns) = (4, 5);
+        Level {
+            name: "Wall".to_string(),
+            rows,
+            columns,
+            spacing: 20.0,
+            bricks: (0..rows)
+                .flat_map(|row| (0..columns).map(move |column| LevelBrick::new(row, column)))
+                .collect(),
+            layouts: Vec::new(),
+        }
+    }
+}
+
+impl Level {
+    /// The brick in the cell at (`row`, `column`).
+    pub fn brick_at(&self, row: usize, column: usize) -> Option<usize> {
+        self.bricks
+            .iter()
+            .position(|brick| brick.row == row && brick.column == column)
+    }
+    /// The bricks which must be broken to clear the level.
+    pub fn breakable_count(&self) -> usize {
+        let laid_out: usize = self.layouts.iter().map(Layout::breakable_count).sum();
+        self.bricks.iter().filter(|b| b.kind.is_breakable()).count() + laid_out
+    }
+    /// The level in the format it is loaded from, a brick per line.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        let config = ron::ser::PrettyConfig::default().depth_limit(2);
+        ron::ser::to_string_pretty(self, config)
+    }
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = self
+            .to_ron()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, content)
+    }
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let level: Level = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// Where the levels of the game in progress come from
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Resource)]
+pub enum LevelSequence {
+    #[default]
+    Campaign,
+    /// a wall generated from the seed for each wave, see [`crate::generator::generate`]
+    Endless { seed: u64 },
+    /// two players across a wall in the middle, see [`crate::versus`]
+    Versus,
+}
+
+/// The levels of the campaign in order
+#[derive(Debug, Default, Resource)]
+pub struct Campaign(pub Vec<Handle<Level>>);
+
+impl Campaign {
+    /// Whether every level finished loading, successfully or not.
+    pub fn is_loaded(&self, asset_server: &AssetServer) -> bool {
+        self.0.iter().all(|handle| {
+            matches!(
+                asset_server.get_load_state(handle),
+                LoadState::Loaded | LoadState::Failed
+            )
+        })
+    }
+    /// The handle of the level numbered `number` from 1, starting over past the last one.
+    pub fn handle(&self, number: usize) -> Option<&Handle<Level>> {
+        if self.0.is_empty() {
+            return None;
+        }
+        self.0.get((number.max(1) - 1) % self.0.len())
+    }
+    /// The level numbered `number` from 1, starting over past the last one.
+    pub fn level(&self, number: usize, levels: &Assets<Level>) -> Level {
+        let loaded: Vec<&Level> = self.0.iter().filter_map(|h| levels.get(h)).collect();
+        if loaded.is_empty() {
+            return Level::default();
+        }
+        loaded[(number.max(1) - 1) % loaded.len()].clone()
+    }
+}
+
+pub fn setup_campaign(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let mut handles: Vec<HandleUntyped> = match asset_server.load_folder(LEVEL_FOLDER) {
+        Ok(handles) => handles,
+        Err(e) => {
+            warn!("failed to load the levels in {}: {}", LEVEL_FOLDER, e);
+            Vec::new()
+        }
+    };
+    handles.sort_by_key(|handle| {
+        asset_server
+            .get_handle_path(handle)
+            .map(|path| path.path().to_owned())
+    });
+    commands.insert_resource(Campaign(
+        handles.into_iter().map(|handle| handle.typed()).collect(),
+    ));
+}
@@ -1 +1,27 @@
+pub mod arena;
+pub mod autopilot;
 pub mod background;
+pub mod bot;
+pub mod coop;
+pub mod editor;
+pub mod env;
+pub mod face;
+pub mod game;
+pub mod generator;
+pub mod hud;
+pub mod import;
+pub mod input;
+pub mod layout;
+pub mod level;
+pub mod menu;
+pub mod pattern;
+pub mod popup;
+pub mod post;
+pub mod records;
+pub mod settings;
+pub mod sound;
+pub mod state;
+pub mod stats;
+pub mod theme;
+pub mod tween;
+pub mod versus;
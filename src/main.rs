@@ -1,660 +1,763 @@
 use {
     bevy::{
+        app::ScheduleRunnerPlugin,
+        ecs::system::SystemParam,
+        input::InputSystem,
+        log::LogPlugin,
         prelude::*,
-        sprite::{
-            collide_aabb::{collide, Collision},
-            Material2dPlugin,
-        },
+        render::{settings::WgpuSettings, texture::DEFAULT_IMAGE_HANDLE, RenderPlugin},
+        sprite::Material2dPlugin,
         // time::fixed_timestep,
-        window::{WindowPlugin, WindowResolution},
+        time::{TimeSystem, TimeUpdateStrategy},
+        window::{ExitCondition, WindowPlugin, WindowResolution},
+        winit::WinitPlugin,
+    },
+    breakout::{
+        arena::{arena_camera_system, Arena, ArenaCamera, Wall, WallSide},
+        autopilot::{autopilot_system, Autopilot, Skill},
+        background::{
+            background_layout_system, background_uniform_system, setup_background, BackgroundFx,
+            BackgroundKind, CustomMaterial,
+        },
+        bot::{bot_system, Bot},
+        coop::Coop,
+        editor::{
+            editor_input_system, editor_mouse_system, editor_view_system, enter_editor_system,
+            exit_editor_system, Editor,
+        },
+        face::{spawn_face, EyeWhite, Face, FacePlugin, Mouth, PaddleEye, MOUTH_DROP},
+        game::{
+            ball_bundle, ball_collision_system, ball_movement_system, lockstep_time,
+            lockstep_time_system, paddle_bundle, paddle_movement_system, serve_position,
+            spawn_bricks, wall_bundle, Ball, Brick, GameEvent, GameRng, LevelIntro, Paddle,
+            WALL_SLIDE,
+        },
+        generator::generate,
+        hud::{
+            bonus_notifier_system, hud_layout_system, hud_theme_system, hud_visibility_system,
+            scoreboard_system, setup_hud, Scoreboard,
+        },
+        import::import_command,
+        input::{action_state_system, ActionState, InputAction},
+        level::{setup_campaign, Campaign, Level, LevelLoader, LevelSequence},
+        menu::{
+            menu_input_system, menu_ui_system, open_title_menu, rebind_system, Menu, MenuScreen,
+            NewGameEvent,
+        },
+        pattern::{setup_brick_patterns, BrickPattern, BrickPatterns},
+        popup::{score_popup_spawner_system, ScorePopupEvent},
+        post::{
+            post_process_system, post_process_uniform_system, setup_post_process, PostProcessFx,
+            PostProcessMaterial,
+        },
+        records::{save_records_system, Records},
+        settings::{motion_settings_system, save_settings_system, Settings},
+        sound::SoundPlugin,
+        state::{pause_time_system, simulating, AppState, Simulation},
+        stats::{statistics_time_system, Statistics},
+        theme::{setup_themes, theme_selection_system, ActiveTheme, Theme, ThemeLoader},
+        tween::{tween_system, Ease, Lens, Motion, Tween, TweenCompleted, Tweens},
+        versus::{versus_level, Player, Versus},
     },
-    breakout::background::{setup_background, CustomMaterial},
     rand::prelude::random,
 };
 
-/// An implementation of the classic game "Breakout"
-const TIME_STEP: f32 = 1.0 / 60.0;
-const SPRITE_Z: f32 = 1.0;
-const BALL_SIZE: f32 = 20.0;
-const EYE_DIST: f32 = 30.0;
+/// how far from the middle the paddles start in co-op
+const COOP_SPREAD: f32 = 150.0;
+/// seconds the game goes on after the last brick or the last life, before its summary
+const SUMMARY_DELAY: f32 = 1.5;
 
+/// An implementation of the classic game "Breakout"
 fn main() {
+    // `breakout import ...` converts an image into a level instead of playing
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|command| command == "import") {
+        if let Err(e) = import_command(&args[1..]) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    // `--autopilot[=novice|average|expert]` lets the computer play, for soak and level testing
+    let mut autopilot = Autopilot::default();
+    for arg in &args {
+        let Some(skill) = arg.strip_prefix("--autopilot") else { continue; };
+        let skill = skill.strip_prefix('=').unwrap_or("expert");
+        let Some(skill) = Skill::named(skill) else {
+            eprintln!("unknown autopilot skill `{}`", skill);
+            std::process::exit(1);
+        };
+        autopilot = Autopilot::new(skill, random());
+        autopilot.playing = true;
+    }
+    // `--bot` hands the paddle over to a program talking JSON lines on stdin and stdout
+    let bot = Bot {
+        connected: args.iter().any(|arg| arg == "--bot"),
+        ..Default::default()
+    };
+    let arena = Arena::default();
+    let window_size = arena.visible_size();
+    let mut plugins = DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: "Breakout+".to_string(),
+            resolution: WindowResolution::new(window_size.x, window_size.y),
+            ..Default::default()
+        }),
+        ..default()
+    });
+    let mut time_update = TimeUpdateStrategy::Automatic;
+    if bot.connected {
+        // no window, no rendering and no logs in the way of stdout, a tick of the game per line
+        plugins = plugins
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                ..default()
+            })
+            .set(RenderPlugin {
+                wgpu_settings: WgpuSettings {
+                    backends: None,
+                    ..default()
+                },
+            })
+            .disable::<WinitPlugin>()
+            .disable::<LogPlugin>()
+            .add(ScheduleRunnerPlugin);
+        time_update = lockstep_time();
+    }
     App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Breakout+".to_string(),
-                resolution: WindowResolution::new(980.0, 710.0),
-                ..Default::default()
-            }),
-            ..default()
-        }))
-        .insert_resource(Scoreboard {
-            score: 0,
-            remain_bricks: 20,
-            brick_in_row: 1,
-            keeping: false,
-            just_changed: None,
-        })
-        .insert_resource(ClearColor(Color::rgb(0.9, 0.9, 0.2)))
+        .add_plugins(plugins)
+        .insert_resource(time_update)
+        .add_system(
+            lockstep_time_system
+                .in_base_set(CoreSet::First)
+                .before(TimeSystem),
+        )
+        .add_state::<AppState>()
+        .init_resource::<Scoreboard>()
+        .init_resource::<GameRng>()
+        .init_resource::<Statistics>()
+        .init_resource::<LevelIntro>()
+        .init_resource::<Editor>()
+        .init_resource::<LevelSequence>()
+        .init_resource::<Versus>()
+        .init_resource::<Coop>()
+        .insert_resource(Settings::load())
+        .insert_resource(Records::load())
+        .insert_resource(arena)
+        .insert_resource(autopilot)
+        .insert_resource(bot)
+        .init_resource::<BackgroundFx>()
+        .init_resource::<PostProcessFx>()
+        .init_resource::<ActionState>()
+        .init_resource::<ActiveTheme>()
+        .init_resource::<Motion>()
+        .init_resource::<Menu>()
+        .add_asset::<Theme>()
+        .init_asset_loader::<ThemeLoader>()
+        .add_asset::<Level>()
+        .init_asset_loader::<LevelLoader>()
         // .add_plugin(ColoredMesh2dPlugin)
         .add_plugin(Material2dPlugin::<CustomMaterial>::default())
+        .add_plugin(Material2dPlugin::<PostProcessMaterial>::default())
+        .add_plugin(FacePlugin)
+        .add_plugin(SoundPlugin)
+        .add_startup_system(setup_themes)
+        .add_startup_system(setup_campaign)
+        .add_startup_system(setup_brick_patterns)
         .add_startup_system(setup_background)
+        .add_startup_system(setup_post_process)
         .add_startup_system(setup)
+        .add_startup_system(setup_hud)
+        .add_event::<ScorePopupEvent>()
+        .add_event::<TweenCompleted>()
+        .add_event::<GameEvent>()
+        .add_event::<NewGameEvent>()
+        .configure_set(Simulation.run_if(simulating))
+        .add_system(open_title_menu.in_schedule(OnEnter(AppState::Title)))
+        .add_system(
+            action_state_system
+                .in_base_set(CoreSet::PreUpdate)
+                .after(InputSystem),
+        )
+        .add_systems(
+            (
+                autopilot_system
+                    .before(paddle_movement_system)
+                    .before(ball_movement_system),
+                paddle_movement_system,
+                ball_collision_system,
+                ball_movement_system,
+                screen_fx_system.after(ball_collision_system),
+            )
+                .in_set(Simulation),
+        )
+        .add_systems(
+            (
+                statistics_time_system,
+                level_end_system.after(ball_collision_system),
+            )
+                .in_set(OnUpdate(AppState::Playing)),
+        )
+        .add_systems(
+            (enter_editor_system, clear_bricks_system).in_schedule(OnEnter(AppState::Editor)),
+        )
+        .add_system(exit_editor_system.in_schedule(OnExit(AppState::Editor)))
+        .add_systems(
+            (
+                editor_input_system,
+                editor_mouse_system,
+                editor_view_system
+                    .after(editor_input_system)
+                    .after(editor_mouse_system),
+            )
+                .in_set(OnUpdate(AppState::Editor)),
+        )
+        .add_system(attract_system.in_set(OnUpdate(AppState::Title)))
+        .add_system(
+            autopilot_menu_system
+                .after(action_state_system)
+                .before(menu_input_system)
+                .run_if(|autopilot: Res<Autopilot>, bot: Res<Bot>| {
+                    autopilot.playing || bot.connected
+                }),
+        )
+        .add_system(
+            bot_system
+                .after(autopilot_system)
+                .before(paddle_movement_system)
+                .before(ball_movement_system)
+                .before(menu_input_system)
+                .run_if(|bot: Res<Bot>| bot.connected),
+        )
         .add_systems((
-            paddle_movement_system,
-            ball_collision_system,
-            ball_movement_system,
-            brick_movement_system,
+            menu_input_system,
+            rebind_system.after(menu_input_system),
+            menu_ui_system.after(rebind_system),
+            pause_time_system,
+            new_game_system,
         ))
         .add_system(scoreboard_system)
         .add_system(bonus_notifier_system)
+        .add_system(score_popup_spawner_system)
+        .add_system(tween_system.after(motion_settings_system))
+        .add_system(motion_settings_system)
+        .add_system(brick_pattern_system)
+        .add_system(background_uniform_system.after(screen_fx_system))
+        .add_system(post_process_uniform_system.after(screen_fx_system))
+        .add_system(post_process_system)
+        .add_system(save_settings_system)
+        .add_system(save_records_system)
+        .add_system(theme_selection_system)
+        .add_system(
+            theme_system
+                .after(theme_selection_system)
+                .before(background_uniform_system),
+        )
+        .add_system(hud_theme_system.after(theme_selection_system))
+        .add_system(hud_visibility_system)
+        .add_system(arena_camera_system)
+        .add_system(arena_layout_system)
+        .add_system(hud_layout_system)
+        .add_system(background_layout_system)
         // .add_system(bevy::input::system::exit_on_all_closed)
         .run();
 }
 
-#[derive(Component)]
-struct Paddle {
-    speed: f32,
-    just_bounced: Option<f32>,
-}
-
-#[derive(Component)]
-struct PaddleEye {
-    is_left: bool,
-}
-
-#[derive(Component)]
-struct Ball {
-    velocity: Vec3,
-    rotation: f32,
-    just_bounced: Option<f32>,
-}
-
-#[derive(Component, Default)]
-struct Brick {
-    velocity: Option<Vec3>,
-    just_bounced: Option<f32>,
-}
-
-#[derive(Component, Eq, PartialEq)]
-enum Collider {
-    Solid,
-    Paddle,
+/// Who plays, on which paddles
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Lineup {
+    Solo,
+    /// the second player at the top, see [`Versus`]
+    Versus,
+    /// the second player beside the first one, see [`Coop`]
+    Coop,
 }
 
-#[derive(Component, Default)]
-struct TextScoreBoard;
-
-#[derive(Component, Default)]
-struct TextBonus {
-    show: Option<f32>,
-    row: usize,
-}
-
-#[derive(Resource)]
-struct Scoreboard {
-    score: usize,
-    remain_bricks: usize,
-    brick_in_row: usize,
-    keeping: bool,
-    just_changed: Option<f32>,
-}
-
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    arena: Res<Arena>,
+    theme: Res<ActiveTheme>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut new_game: EventWriter<NewGameEvent>,
+) {
+    let theme = &theme.0;
     // Add the game's entities to our world
 
     // cameras
     // commands.spawn_bundle(OrthographicCameraBundle::new_2d());
-    commands.spawn(Camera2dBundle::default());
-    // commands.spawn_bundle(UiCameraBundle::default());
-    // paddle
-    commands
-        .spawn(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(0.0, -230.0, SPRITE_Z),
-                scale: Vec3::new(120.0, 30.0, 0.0),
-                ..Default::default()
-            },
-            sprite: Sprite {
-                color: Color::rgb(0.5, 0.5, 1.0),
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(Paddle {
-            speed: 500.0,
-            just_bounced: None,
-        })
-        .insert(Collider::Paddle);
-    // paddle left eye
-    commands
-        .spawn(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(-EYE_DIST, -230.0, SPRITE_Z + 0.1),
-                scale: Vec3::new(0.25, 0.25, 0.0),
-                ..Default::default()
-            },
-            texture: asset_server.load("sprites/eye.png"),
-            ..Default::default()
-        })
-        .insert(PaddleEye { is_left: true });
-
-    // paddle left black eye
-    commands
-        .spawn(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(-EYE_DIST, -230.0, SPRITE_Z + 0.2),
-                scale: Vec3::new(0.25, 0.25, 0.0),
-                ..Default::default()
-            },
-            texture: asset_server.load("sprites/black-eye.png"),
-            ..Default::default()
-        })
-        .insert(PaddleEye { is_left: true });
-
-    // paddle right eye
     commands
-        .spawn(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(EYE_DIST, -230.0, SPRITE_Z),
-                scale: Vec3::new(0.25, 0.25, 0.0),
+        .spawn(Camera2dBundle {
+            projection: OrthographicProjection {
+                scaling_mode: arena.scaling_mode(),
                 ..Default::default()
             },
-            texture: asset_server.load("sprites/eye.png"),
             ..Default::default()
         })
-        .insert(PaddleEye { is_left: false });
+        .insert(ArenaCamera);
+    // commands.spawn_bundle(UiCameraBundle::default());
+    // Add walls
+    for side in [
+        WallSide::Left,
+        WallSide::Right,
+        WallSide::Bottom,
+        WallSide::Top,
+    ] {
+        commands.spawn(wall_bundle(&arena, side, theme));
+    }
 
-    // paddle right black eye
-    commands
-        .spawn(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(EYE_DIST, -230.0, SPRITE_Z + 0.2),
-                scale: Vec3::new(0.25, 0.25, 0.0),
-                ..Default::default()
-            },
-            texture: asset_server.load("sprites/black-eye.png"),
-            ..Default::default()
-        })
-        .insert(PaddleEye { is_left: false });
-    // ball
-    commands
-        .spawn(SpriteBundle {
-            transform: Transform {
-                scale: Vec3::new(BALL_SIZE, BALL_SIZE, 0.0),
-                translation: Vec3::new(0.0, -50.0, SPRITE_Z),
-                ..Default::default()
-            },
-            sprite: Sprite {
-                color: Color::rgb(0.2, 0.3, 1.0),
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(Ball {
-            velocity: 400.0 * Vec3::new(0.5, -0.5, 0.0).normalize(),
-            rotation: 0.0,
-            just_bounced: None,
-        });
-    // scoreboard
-    commands
-        .spawn(TextBundle {
-            text: Text {
-                sections: vec![
-                    TextSection {
-                        value: "Score: ".to_string(),
-                        style: TextStyle {
-                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                            font_size: 40.0,
-                            color: Color::rgb(0.5, 0.5, 1.0),
-                        },
-                    },
-                    TextSection {
-                        value: "".to_string(),
-                        style: TextStyle {
-                            font: asset_server.load("fonts/FiraMono-Medium.ttf"),
-                            font_size: 40.0,
-                            color: Color::rgb(1.0, 0.5, 0.5),
-                        },
-                    },
-                ],
-                ..Default::default()
-            },
-            style: Style {
-                position_type: PositionType::Absolute,
-                position: UiRect {
-                    top: Val::Percent(60.0),
-                    left: Val::Percent(45.0),
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(TextScoreBoard::default());
+    spawn_play_field(
+        &mut commands,
+        &asset_server,
+        &arena,
+        theme,
+        &mut meshes,
+        &mut materials,
+        Lineup::Solo,
+    );
+    // the bricks come once the campaign is loaded
+    new_game.send(NewGameEvent::Attract);
+}
 
-    // bonus notifier
-    commands
-        .spawn(TextBundle {
-            text: Text {
-                linebreak_behaviour: bevy::text::BreakLineOn::AnyCharacter,
-                sections: vec![TextSection {
-                    value: "+1".to_string(),
-                    style: TextStyle {
-                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                        // font_size: 100.0,
-                        color: Color::rgba(1.0, 0.2, 0.0, 0.8),
-                        ..default()
-                    },
-                }],
-                alignment: TextAlignment::Center,
-            },
-            style: Style {
-                position_type: PositionType::Absolute,
-                position: UiRect {
-                    top: Val::Percent(35.0),
-                    left: Val::Percent(40.0),
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(TextBonus::default());
+/// Where the bricks of a new game come from
+#[derive(SystemParam)]
+struct LevelSource<'w> {
+    asset_server: Res<'w, AssetServer>,
+    arena: Res<'w, Arena>,
+    campaign: Res<'w, Campaign>,
+    levels: Res<'w, Assets<Level>>,
+    editor: Res<'w, Editor>,
+    sequence: ResMut<'w, LevelSequence>,
+}
 
-    // Add walls
-    let wall_color = Color::rgb(0.8, 0.8, 0.8);
-    let wall_thickness = 35.0;
-    let bounds = Vec2::new(960.0, 680.0);
+impl LevelSource<'_> {
+    fn is_loaded(&self) -> bool {
+        self.campaign.is_loaded(&self.asset_server)
+    }
+    /// The level numbered `number` in the sequence, or the one in the editor while play-testing.
+    fn level(&self, number: usize) -> Level {
+        if self.editor.play_testing {
+            return self.editor.level.clone();
+        }
+        match *self.sequence {
+            LevelSequence::Campaign => self.campaign.level(number, &self.levels),
+            LevelSequence::Endless { seed } => generate(seed, number),
+            LevelSequence::Versus => versus_level(&self.arena),
+        }
+    }
+}
 
-    // left
-    commands
-        .spawn(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(-bounds.x / 2.0, 0.0, SPRITE_Z),
-                scale: Vec3::new(wall_thickness, bounds.y + wall_thickness, 1.0),
-                ..Default::default()
-            },
-            sprite: Sprite {
-                color: wall_color,
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(Collider::Solid);
-    // right
-    commands
-        .spawn(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(bounds.x / 2.0, 0.0, SPRITE_Z),
-                scale: Vec3::new(wall_thickness, bounds.y + wall_thickness, 1.0),
-                ..Default::default()
-            },
-            sprite: Sprite {
-                color: wall_color,
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(Collider::Solid);
-    // bottom
-    commands
-        .spawn(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(0.0, -bounds.y / 2.0, SPRITE_Z),
-                scale: Vec3::new(bounds.x + wall_thickness, wall_thickness, 1.0),
-                ..Default::default()
-            },
-            sprite: Sprite {
-                color: wall_color,
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(Collider::Solid);
-    // top
-    commands
-        .spawn(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(0.0, bounds.y / 2.0, SPRITE_Z),
-                scale: Vec3::new(bounds.x + wall_thickness, wall_thickness, 1.0),
-                ..Default::default()
-            },
-            sprite: Sprite {
-                color: wall_color,
-                ..Default::default()
-            },
+/// Start a new game or the next level when asked for it, once the campaign is loaded.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn new_game_system(
+    mut commands: Commands,
+    mut events: EventReader<NewGameEvent>,
+    mut pending: Local<Option<NewGameEvent>>,
+    mut first_level: Local<usize>,
+    mut source: LevelSource,
+    arena: Res<Arena>,
+    theme: Res<ActiveTheme>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut versus: ResMut<Versus>,
+    mut coop: ResMut<Coop>,
+    mut statistics: ResMut<Statistics>,
+    mut intro: ResMut<LevelIntro>,
+    walls: Query<(Entity, &Wall, &Transform)>,
+    play_field: Query<
+        Entity,
+        Or<(
+            With<Paddle>,
+            With<PaddleEye>,
+            With<Mouth>,
+            With<Ball>,
+            With<Brick>,
+        )>,
+    >,
+) {
+    if let Some(&event) = events.iter().last() {
+        *pending = Some(event);
+    }
+    if pending.is_none() || !source.is_loaded() {
+        return;
+    }
+    let Some(event) = pending.take() else { return; };
+    for entity in play_field.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if event == NewGameEvent::NextLevel {
+        *scoreboard = Scoreboard {
+            score: scoreboard.score,
+            lives: scoreboard.lives,
+            level: scoreboard.level + 1,
             ..Default::default()
-        })
-        .insert(Collider::Solid);
-
-    // Add bricks
-    let brick_rows = 4;
-    let brick_columns = 5;
-    let brick_spacing = 20.0;
-    let brick_size = Vec3::new(150.0, 30.0, 1.0);
-    let bricks_width = brick_columns as f32 * (brick_size.x + brick_spacing) - brick_spacing;
-    // center the bricks and move them up a bit
-    let bricks_offset = Vec3::new(-(bricks_width - brick_size.x) / 2.0, 100.0, 0.0);
-    let brick_color = Color::rgb(0.5, 0.5, 1.0);
-    for row in 0..brick_rows {
-        let y_position = row as f32 * (brick_size.y + brick_spacing);
-        for column in 0..brick_columns {
-            let brick_position = Vec3::new(
-                column as f32 * (brick_size.x + brick_spacing),
-                y_position,
-                SPRITE_Z,
-            ) + bricks_offset;
-            // brick
-            commands
-                .spawn(SpriteBundle {
-                    sprite: Sprite {
-                        color: brick_color,
-                        ..Default::default()
-                    },
-                    transform: Transform {
-                        translation: brick_position,
-                        scale: brick_size,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                })
-                // .insert(Collider::Scorable)
-                .insert(Brick {
-                    just_bounced: None,
-                    ..Default::default()
-                });
+        };
+        statistics.next_level(scoreboard.score);
+        coop.next_level();
+    } else {
+        *first_level = match event {
+            NewGameEvent::Start { level } => level,
+            NewGameEvent::Endless { .. }
+            | NewGameEvent::Versus
+            | NewGameEvent::Coop
+            | NewGameEvent::Attract => 1,
+            _ => (*first_level).max(1),
+        };
+        match event {
+            NewGameEvent::Endless { seed } => *source.sequence = LevelSequence::Endless { seed },
+            NewGameEvent::Versus => *source.sequence = LevelSequence::Versus,
+            NewGameEvent::Start { .. } | NewGameEvent::Coop | NewGameEvent::Attract => {
+                *source.sequence = LevelSequence::Campaign
+            }
+            _ => (),
         }
+        // a restart keeps both players
+        *coop = match event {
+            NewGameEvent::Coop => Coop::start(),
+            NewGameEvent::Restart if coop.active => Coop::start(),
+            _ => Coop::default(),
+        };
+        *scoreboard = Scoreboard {
+            level: *first_level,
+            ..Default::default()
+        };
+        *statistics = Statistics::default();
     }
+    let level = source.level(scoreboard.level);
+    *versus = if *source.sequence == LevelSequence::Versus {
+        Versus::start(&level, &arena, scoreboard.lives)
+    } else {
+        Versus::default()
+    };
+    let lineup = if versus.active {
+        Lineup::Versus
+    } else if coop.active {
+        Lineup::Coop
+    } else {
+        Lineup::Solo
+    };
+    spawn_play_field(
+        &mut commands,
+        &source.asset_server,
+        &arena,
+        &theme.0,
+        &mut meshes,
+        &mut materials,
+        lineup,
+    );
+    // slide the walls in from beyond the screen, then let the bricks fly in
+    for (entity, wall, transform) in walls.iter() {
+        let outward = match wall.0 {
+            WallSide::Left => Vec2::NEG_X,
+            WallSide::Right => Vec2::X,
+            WallSide::Bottom => Vec2::NEG_Y,
+            WallSide::Top => Vec2::Y,
+        };
+        let end = arena.wall(wall.0).center().extend(transform.translation.z);
+        let start = end + (outward * arena.viewport / 2.0).extend(0.0);
+        commands.entity(entity).insert(Tweens::new(Tween::new(
+            Lens::Translation { start, end },
+            Ease::CubicOut,
+            WALL_SLIDE,
+        )));
+    }
+    intro.0 = spawn_bricks(&mut commands, &arena, &theme.0, &level, &mut scoreboard);
 }
 
-#[allow(clippy::type_complexity)]
-fn paddle_movement_system(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut queries: ParamSet<(
-        Query<(&mut Paddle, &mut Transform)>,
-        Query<(&mut PaddleEye, &mut Transform)>,
-    )>,
+/// Play behind the title screen with the autopilot, starting over once cleared.
+fn attract_system(
+    scoreboard: Res<Scoreboard>,
+    versus: Res<Versus>,
+    coop: Res<Coop>,
+    mut new_game: EventWriter<NewGameEvent>,
 ) {
-    let mut paddle = queries.p0();
-    let (mut paddle, mut transform) = paddle.single_mut();
-    let mut direction = 0.0;
-    if keyboard_input.pressed(KeyCode::Left) {
-        direction -= 1.0;
+    // a game of two left for the title screen is no game for the autopilot alone
+    if 0 == scoreboard.remain_bricks || 0 == scoreboard.lives || versus.active || coop.active {
+        new_game.send(NewGameEvent::Attract);
     }
+}
 
-    if keyboard_input.pressed(KeyCode::Right) {
-        direction += 1.0;
+/// Go on to the next game from the title and summary screens when the autopilot or a bot plays.
+fn autopilot_menu_system(
+    time: Res<Time>,
+    state: Res<State<AppState>>,
+    mut waited: Local<f32>,
+    mut actions: ResMut<ActionState>,
+) {
+    if !matches!(
+        state.0,
+        AppState::Title | AppState::LevelCleared | AppState::GameOver
+    ) {
+        *waited = 0.0;
+        return;
     }
-
-    let translation = &mut transform.translation;
-    // move the paddle horizontally
-    translation.x += direction * paddle.speed * TIME_STEP;
-    let p_pos: f32 = translation.x;
-    // bound the paddle within the walls
-    translation.x = translation.x.clamp(-400.0, 400.0);
-    let just_bounced: Option<f32> = paddle.just_bounced;
-    if let Some(ref mut t) = paddle.just_bounced {
-        if 0.1 < *t {
-            *t *= 0.8;
-        } else {
-            paddle.just_bounced = None;
-        }
+    *waited += time.delta_seconds();
+    if SUMMARY_DELAY < *waited {
+        *waited = 0.0;
+        actions.press(InputAction::Confirm);
     }
+}
 
-    // move eyes
-    let mut eyes = queries.p1();
-    for (eye, mut trans) in eyes.iter_mut() {
-        trans.translation.x = if eye.is_left {
-            p_pos - EYE_DIST
-        } else {
-            p_pos + EYE_DIST
+/// Sum up the level once cleared, or the run once out of lives, after a moment.
+#[allow(clippy::too_many_arguments)]
+fn level_end_system(
+    time: Res<Time>,
+    mut events: EventReader<GameEvent>,
+    mut pending: Local<Option<(MenuScreen, f32)>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut menu: ResMut<Menu>,
+    scoreboard: Res<Scoreboard>,
+    mut statistics: ResMut<Statistics>,
+    mut records: ResMut<Records>,
+    editor: Res<Editor>,
+    autopilot: Res<Autopilot>,
+    bot: Res<Bot>,
+    sequence: Res<LevelSequence>,
+    versus: Res<Versus>,
+    mut coop: ResMut<Coop>,
+) {
+    for event in events.iter() {
+        let screen = match event {
+            GameEvent::LevelCleared => MenuScreen::LevelCleared,
+            GameEvent::GameOver => MenuScreen::GameOver,
+            _ => continue,
         };
-        let Some(ref t) = just_bounced else { continue; };
-        if 0.1 < *t {
-            trans.scale.x = 0.25 + 0.5 * *t;
-            trans.scale.y = 0.25 + 0.5 * *t;
-        } else {
-            trans.scale.x = 0.25;
-            trans.scale.y = 0.25;
-        }
+        pending.get_or_insert((screen, SUMMARY_DELAY));
+    }
+    let Some((screen, delay)) = pending.as_mut() else { return; };
+    *delay -= time.delta_seconds();
+    if 0.0 < *delay {
+        return;
+    }
+    let screen = *screen;
+    *pending = None;
+    // the game may have been replaced from the pause menu in the meantime
+    let (state, ended) = match screen {
+        MenuScreen::GameOver => (
+            AppState::GameOver,
+            0 == scoreboard.lives || versus.winner.is_some(),
+        ),
+        _ => (AppState::LevelCleared, 0 == scoreboard.remain_bricks),
+    };
+    if !ended {
+        return;
+    }
+    statistics.finish_level(scoreboard.score);
+    coop.finish_level();
+    next_state.set(state);
+    menu.open(screen);
+    // play-tests and games of two don't count, and the autopilot reports how the level went
+    // instead
+    if autopilot.playing {
+        let stats = &statistics.level;
+        info!(
+            "autopilot level {}: {}, score {}, bricks {}, penalties {}, {:.0} s",
+            scoreboard.level,
+            if state == AppState::GameOver {
+                "lost"
+            } else {
+                "cleared"
+            },
+            stats.score,
+            stats.bricks,
+            stats.penalties,
+            stats.time,
+        );
+    }
+    if editor.play_testing
+        || autopilot.playing
+        || bot.connected
+        || *sequence == LevelSequence::Versus
+        || coop.active
+    {
+        return;
+    }
+    if state == AppState::GameOver {
+        records.record(scoreboard.score, scoreboard.level);
+    } else if *sequence == LevelSequence::Campaign && records.highest_level <= scoreboard.level {
+        records.highest_level = scoreboard.level + 1;
     }
 }
 
-fn ball_movement_system(mut ball_query: Query<(&mut Ball, &mut Transform)>) {
-    let (mut ball, mut transform) = ball_query.single_mut();
-    let vel = ball.velocity * TIME_STEP;
-    transform.translation += vel;
-    // transform.rotation = transform.rotation.add(Quat::from_rotation_x(0.01));
-    ball.rotation += 8.0 * TIME_STEP;
-    transform.rotation = Quat::from_rotation_z(ball.rotation);
-    let Some(ref mut t) = ball.just_bounced else { return; };
-    // double speed
-    transform.translation += 0.3 * vel;
-    const SCALE: f32 = 0.95;
-    transform.scale = Vec3::new(BALL_SIZE * (1.0 + *t), BALL_SIZE * (1.0 + *t), 0.0);
-    if 1.0 - SCALE < *t {
-        *t *= SCALE;
-    } else {
-        ball.just_bounced = None;
+/// Clear the field for the editor, which draws its own bricks.
+fn clear_bricks_system(mut commands: Commands, bricks: Query<Entity, With<Brick>>) {
+    for entity in bricks.iter() {
+        commands.entity(entity).despawn_recursive();
     }
 }
 
-fn scoreboard_system(
-    mut scoreboard: ResMut<Scoreboard>,
-    mut query: Query<(&mut Text, &mut Style), With<TextScoreBoard>>,
+/// Spawn the paddles of the `lineup` and the ball.
+fn spawn_play_field(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    arena: &Arena,
+    theme: &Theme,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    lineup: Lineup,
 ) {
-    let (mut text, mut style) = query.single_mut();
-    let remains = scoreboard.remain_bricks;
-    let score = scoreboard.score;
-    if let Some(ref mut t) = scoreboard.just_changed {
-        style.display = Display::Flex;
-        if 0.1 < *t {
-            text.sections[1].value = format!("{}", score);
-            if 0 < remains {
-                *t *= 0.9;
-            }
-        } else {
-            scoreboard.just_changed = None;
-        }
-    } else {
-        style.display = Display::None;
+    // the paddles at the bottom have a face, the one at the top in versus goes without
+    let bottom = match lineup {
+        Lineup::Coop => vec![(Player::One, -COOP_SPREAD), (Player::Two, COOP_SPREAD)],
+        Lineup::Solo | Lineup::Versus => vec![(Player::One, 0.0)],
+    };
+    if lineup == Lineup::Versus {
+        commands.spawn(paddle_bundle(arena, theme, Player::Two, WallSide::Top, 0.0));
     }
+    let serve = serve_position(Vec2::new(bottom[0].1, arena.paddle_y()));
+    for (player, x) in bottom {
+        let face = commands
+            .spawn(paddle_bundle(arena, theme, player, WallSide::Bottom, x))
+            .insert(Face::default())
+            .id();
+        let position = Vec2::new(x, arena.paddle_y());
+        spawn_face(
+            commands,
+            asset_server,
+            theme,
+            meshes,
+            materials,
+            face,
+            player,
+            position,
+        );
+    }
+    // ball, resting on the paddle of the first player until launched
+    commands.spawn(ball_bundle(theme, serve, true));
 }
 
-fn bonus_notifier_system(
-    mut bonus_query: Query<(&mut Text, &mut Style, &mut TextBonus, &mut Transform)>,
+/// Feed the background with the ball position, the combo and a flash on every hit.
+fn screen_fx_system(
+    state: Res<State<AppState>>,
+    mut events: EventReader<GameEvent>,
+    motion: Res<Motion>,
+    scoreboard: Res<Scoreboard>,
+    ball_query: Query<&Transform, With<Ball>>,
+    mut fx: ResMut<BackgroundFx>,
+    mut post_fx: ResMut<PostProcessFx>,
 ) {
-    let (mut text, mut style, mut bonus, mut transform) = bonus_query.single_mut();
-    let point = bonus.row;
-    if let Some(ref mut t) = bonus.show {
-        style.display = Display::Flex;
-        if 0.1 < *t {
-            text.sections[0].value = format!("+{}", point);
-            text.sections[0].style.color = Color::rgba(1.0, 0.2, 0.0, t.sqrt());
-            let s = 5.0 * (2.0 - *t);
-            transform.scale = Vec3::new(s, s, 1.0);
-            *t *= 0.9;
-        } else {
-            bonus.show = None;
-        }
+    for event in events.iter() {
+        let flash = match event {
+            GameEvent::BrickHit { .. } => 1.0,
+            GameEvent::LevelCleared | GameEvent::GameOver => 1.0,
+            GameEvent::Penalty => 0.6,
+            GameEvent::NearMiss => 0.3,
+            GameEvent::PaddleHit { .. } => continue,
+        };
+        let flash = flash * motion.0;
+        fx.flash = fx.flash.max(flash);
+        post_fx.aberration = post_fx.aberration.max(flash);
+    }
+    fx.combo = scoreboard.brick_in_row;
+    fx.kind = if state.0 == AppState::Title {
+        BackgroundKind::Starfield
     } else {
-        style.display = Display::None;
+        BackgroundKind::for_level(scoreboard.level)
+    };
+    if let Ok(transform) = ball_query.get_single() {
+        fx.ball = transform.translation.truncate();
     }
 }
 
-fn brick_movement_system(
-    mut commands: Commands,
-    mut bricks: Query<(Entity, &mut Brick, &mut Transform)>,
+/// Repaint the arena when the theme changes.
+#[allow(clippy::type_complexity)]
+fn theme_system(
+    theme: Res<ActiveTheme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut fx: ResMut<BackgroundFx>,
+    mut sprites: Query<
+        (
+            &mut Sprite,
+            Option<&Brick>,
+            Option<&Ball>,
+            Option<&Wall>,
+            Option<&Paddle>,
+        ),
+        (
+            Or<(With<Paddle>, With<Ball>, With<Wall>, With<Brick>)>,
+            Without<EyeWhite>,
+        ),
+    >,
+    mut eyes: Query<(&mut Sprite, &EyeWhite)>,
+    mouths: Query<&Handle<ColorMaterial>, With<Mouth>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    const SCALE: f32 = 0.94;
-    for (entity, mut brick, mut trans) in bricks.iter_mut() {
-        let velocity = brick.velocity;
-        let Some(ref mut t) = &mut brick.just_bounced else { continue; };
-        if 1.0 - SCALE < *t {
-            *t *= SCALE;
-            if let Some(v) = velocity {
-                trans.translation += *t * 0.6 * TIME_STEP * v;
-            }
-            trans.rotation = Quat::from_rotation_z(0.4 * random::<f32>());
-            trans.scale *= 0.99; // SCALE;
-        } else {
-            // scorable colliders should be despawned and increment the scoreboard on collision
-            commands.entity(entity).despawn();
+    if !theme.is_changed() {
+        return;
+    }
+    let theme = &theme.0;
+    clear_color.0 = theme.background.with_a(1.0);
+    fx.tint = theme.background;
+    for (mut sprite, brick, ball, wall, paddle) in sprites.iter_mut() {
+        sprite.color = match (brick, ball, wall, paddle) {
+            (Some(brick), _, _, _) => brick.spec.color(theme, brick.rows),
+            (_, Some(_), _, _) => theme.ball,
+            (_, _, Some(_), _) => theme.walls,
+            (_, _, _, Some(paddle)) => theme.player_paddle(paddle.player),
+            _ => theme.paddle,
+        };
+    }
+    for (mut sprite, eye) in eyes.iter_mut() {
+        sprite.color = theme.player_eyes(eye.player);
+    }
+    for handle in mouths.iter() {
+        if let Some(material) = materials.get_mut(handle) {
+            material.color = theme.mouth;
         }
     }
 }
 
-fn ball_collision_system(
-    mut scoreboard: ResMut<Scoreboard>,
-    mut paddle_query: Query<&mut Paddle>,
-    mut ball_query: Query<(&mut Ball, &Transform)>,
-    mut brick_query: Query<(&mut Brick, &Transform)>,
-    mut bonus_query: Query<&mut TextBonus>,
-    collider_query: Query<(&Collider, &Transform)>,
+/// Texture the bricks with a pattern per kind when the setting asks for it.
+fn brick_pattern_system(
+    settings: Res<Settings>,
+    patterns: Res<BrickPatterns>,
+    mut bricks: Query<(Ref<Brick>, &mut Sprite, &mut Handle<Image>)>,
 ) {
-    let (mut ball, ball_transform) = ball_query.single_mut();
-    let ball_size = ball_transform.scale.truncate();
-    let velocity = &mut ball.velocity;
-    let mut penalty = 0;
-    let mut collided = false;
-    let mut collided_with_paddle = false;
-    let mut score_changed = false;
-
-    // check collision with walls
-    for (collider, transform) in collider_query.iter() {
-        let collision = collide(
-            ball_transform.translation,
-            ball_size,
-            transform.translation,
-            transform.scale.truncate(),
-        );
-        let Some(collision) = collision else { continue; };
-        collided = true;
-        scoreboard.keeping = false;
-
-        // reflect the ball when it collides
-        let mut reflect_x = false;
-        let mut reflect_y = false;
-
-        // only reflect if the ball's velocity is going in the opposite direction of the
-        // collision
-        match collision {
-            Collision::Left => reflect_x = velocity.x > 0.0,
-            Collision::Right => reflect_x = velocity.x < 0.0,
-            Collision::Top => reflect_y = velocity.y < 0.0,
-            Collision::Bottom => reflect_y = velocity.y > 0.0,
-            Collision::Inside => (),
-        }
-
-        // reflect velocity on the x-axis if we hit something on the x-axis
-        if reflect_x {
-            velocity.x = -velocity.x + random::<f32>() * 2.0;
-        }
-
-        // reflect velocity on the y-axis if we hit something on the y-axis
-        if reflect_y {
-            velocity.y = -velocity.y + random::<f32>() * 2.0;
-        }
-
-        if let Collider::Paddle = *collider {
-            if matches!(collision, Collision::Bottom) {
-                penalty = 2;
-            }
-            collided_with_paddle = true;
-        } else if let Collider::Solid = *collider {
-            // break if this collide is on a solid, otherwise continue check
-            // whether a solid is also in collision
-            if matches!(collision, Collision::Top) && reflect_y {
-                scoreboard.brick_in_row = 1;
-                scoreboard.keeping = false;
-                penalty = penalty.max(1);
-            }
-            break;
-        }
-    }
-    match penalty {
-        _ if 0 == scoreboard.remain_bricks => (),
-        2 => {
-            scoreboard.score /= 2;
-            score_changed = true;
-        }
-        1 if 0 < scoreboard.score => {
-            scoreboard.score -= 1;
-            score_changed = true;
-        }
-        _ => (),
-    }
-    // check collision with brick
-    for (mut brick, transform) in brick_query.iter_mut() {
-        let collision = collide(
-            ball_transform.translation,
-            ball_size,
-            transform.translation,
-            transform.scale.truncate(),
-        );
-        let Some(collision) = collision else { continue; };
-        if brick.just_bounced.is_some() {
+    for (brick, mut sprite, mut texture) in bricks.iter_mut() {
+        // a cracked brick may change pattern
+        if !settings.is_changed() && !brick.is_changed() {
             continue;
         }
-        collided = true;
-        if 0 < scoreboard.remain_bricks {
-            if scoreboard.keeping {
-                scoreboard.brick_in_row += 1;
-                if 1 < scoreboard.brick_in_row {
-                    let mut bonus = bonus_query.single_mut();
-                    bonus.row = scoreboard.brick_in_row;
-                    bonus.show = Some(2.0);
-                }
-            }
-            scoreboard.keeping = true;
-            scoreboard.score += scoreboard.brick_in_row;
-            scoreboard.remain_bricks -= 1;
-            if 0 == scoreboard.remain_bricks {
-                scoreboard.just_changed = Some(100.0);
-            }
-            score_changed = true;
-        }
-        // commands.entity(collider_entity).despawn();
-        if brick.just_bounced.is_none() {
-            brick.velocity = Some(*velocity);
-            brick.just_bounced = Some(1.0);
-        }
-
-        // reflect the ball when it collides
-        let mut reflect_x = false;
-        let mut reflect_y = false;
-
-        // only reflect if the ball's velocity is going in the opposite direction of the
-        // collision
-        match collision {
-            Collision::Left => reflect_x = velocity.x > 0.0,
-            Collision::Right => reflect_x = velocity.x < 0.0,
-            Collision::Top => reflect_y = velocity.y < 0.0,
-            Collision::Bottom => reflect_y = velocity.y > 0.0,
-            Collision::Inside => (),
-        }
-
-        // reflect velocity on the x-axis if we hit something on the x-axis
-        if reflect_x {
-            velocity.x = -velocity.x;
-        }
+        *texture = if settings.brick_patterns {
+            patterns.get(BrickPattern::for_brick(brick.spec.kind, brick.spec.hp))
+        } else {
+            DEFAULT_IMAGE_HANDLE.typed()
+        };
+        // the textures are stretched over the brick like the default 1x1 one
+        sprite.custom_size = Some(Vec2::ONE);
+    }
+}
 
-        // reflect velocity on the y-axis if we hit something on the y-axis
-        if reflect_y {
-            velocity.y = -velocity.y;
-        }
+/// Move the walls and the paddle when the arena geometry changes.
+#[allow(clippy::type_complexity)]
+fn arena_layout_system(
+    arena: Res<Arena>,
+    mut walls: Query<(&Wall, &mut Transform)>,
+    mut paddles: Query<
+        (&mut Transform, Option<&Mouth>, Option<&Paddle>),
+        (
+            Or<(With<Paddle>, With<PaddleEye>, With<Mouth>)>,
+            Without<Wall>,
+        ),
+    >,
+) {
+    if !arena.is_changed() || arena.is_added() {
+        return;
     }
-    if collided {
-        ball.just_bounced = Some(1.0);
+    for (wall, mut transform) in walls.iter_mut() {
+        let rect = arena.wall(wall.0);
+        transform.translation = rect.center().extend(transform.translation.z);
+        transform.scale = rect.size().extend(1.0);
     }
-    // check collision with paddle
-    if collided_with_paddle {
-        for mut paddle in paddle_query.iter_mut() {
-            paddle.just_bounced = Some(1.0);
+    for (mut transform, mouth, paddle) in paddles.iter_mut() {
+        if paddle.is_some_and(|paddle| paddle.goal == WallSide::Top) {
+            transform.translation.y = arena.top_paddle_y();
+            continue;
         }
-    }
-    if score_changed && scoreboard.just_changed.is_none() {
-        scoreboard.just_changed = Some(4.0);
+        let drop = if mouth.is_some() { MOUTH_DROP } else { 0.0 };
+        transform.translation.y = arena.paddle_y() - drop;
     }
 }
@@ -0,0 +1,603 @@
+use {
+    crate::{
+        autopilot::Autopilot,
+        bot::Bot,
+        coop::Coop,
+        editor::Editor,
+        hud::Scoreboard,
+        input::{ActionState, Binding, InputAction},
+        records::Records,
+        settings::{Difficulty, Settings},
+        state::AppState,
+        stats::Statistics,
+        theme::{ActiveTheme, Theme},
+        versus::Versus,
+    },
+    bevy::{app::AppExit, prelude::*},
+    rand::prelude::random,
+};
+
+const LOGO_FONT_SIZE: f32 = 96.0;
+const TITLE_FONT_SIZE: f32 = 64.0;
+const ITEM_FONT_SIZE: f32 = 36.0;
+const VOLUME_STEP: f32 = 0.1;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum MenuScreen {
+    #[default]
+    Title,
+    LevelSelect,
+    HighScores,
+    Pause,
+    Settings,
+    Bindings,
+    LevelCleared,
+    GameOver,
+}
+
+impl MenuScreen {
+    fn title(self) -> &'static str {
+        match self {
+            MenuScreen::Title => "Breakout+",
+            MenuScreen::LevelSelect => "Level select",
+            MenuScreen::HighScores => "High scores",
+            MenuScreen::Pause => "Paused",
+            MenuScreen::Settings => "Settings",
+            MenuScreen::Bindings => "Bindings",
+            MenuScreen::LevelCleared => "Level cleared",
+            MenuScreen::GameOver => "Game over",
+        }
+    }
+    /// The items of the screen, leading back to the editor instead of the title while
+    /// `play_testing`.
+    fn items(self, records: &Records, play_testing: bool) -> Vec<MenuItem> {
+        let quit = if play_testing {
+            MenuItem::BackToEditor
+        } else {
+            MenuItem::QuitToTitle
+        };
+        match self {
+            MenuScreen::Title => vec![
+                MenuItem::Play,
+                MenuItem::Endless,
+                MenuItem::Versus,
+                MenuItem::Coop,
+                MenuItem::LevelSelect,
+                MenuItem::HighScores,
+                MenuItem::Editor,
+                MenuItem::Settings,
+                MenuItem::Quit,
+            ],
+            MenuScreen::LevelSelect => (1..=records.highest_level.max(1))
+                .map(MenuItem::Level)
+                .chain([MenuItem::Back])
+                .collect(),
+            MenuScreen::HighScores => vec![MenuItem::Back],
+            MenuScreen::Pause => vec![
+                MenuItem::Resume,
+                MenuItem::Restart,
+                MenuItem::Settings,
+                quit,
+            ],
+            MenuScreen::Settings => vec![
+                MenuItem::Volume,
+                MenuItem::Difficulty,
+                MenuItem::Theme,
+                MenuItem::ReducedMotion,
+                MenuItem::BrickPatterns,
+                MenuItem::Crt,
+                MenuItem::ChromaticAberration,
+                MenuItem::Vignette,
+                MenuItem::Glow,
+                MenuItem::Bindings,
+                MenuItem::Back,
+            ],
+            MenuScreen::Bindings => InputAction::ALL
+                .iter()
+                .map(|&action| MenuItem::Rebind(action))
+                .chain([MenuItem::Back])
+                .collect(),
+            MenuScreen::LevelCleared if play_testing => vec![quit],
+            MenuScreen::LevelCleared => vec![MenuItem::NextLevel, quit],
+            MenuScreen::GameOver => vec![MenuItem::Restart, quit],
+        }
+    }
+    /// The text shown between the title and the items
+    fn lines(
+        self,
+        records: &Records,
+        statistics: &Statistics,
+        versus: &Versus,
+        coop: &Coop,
+    ) -> Vec<String> {
+        match self {
+            MenuScreen::HighScores if records.high_scores.is_empty() => {
+                vec!["No games played yet".to_string()]
+            }
+            MenuScreen::HighScores => records
+                .high_scores
+                .iter()
+                .enumerate()
+                .map(|(i, h)| format!("{:>2}. {:>6}  level {}", i + 1, h.score, h.level))
+                .collect(),
+            MenuScreen::LevelCleared if coop.active => {
+                [statistics.level.lines(), Coop::lines(&coop.level)].concat()
+            }
+            MenuScreen::LevelCleared => statistics.level.lines(),
+            MenuScreen::GameOver if versus.active => versus.lines(),
+            MenuScreen::GameOver if coop.active => {
+                [statistics.run.lines(), Coop::lines(&coop.run)].concat()
+            }
+            MenuScreen::GameOver => statistics.run.lines(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MenuItem {
+    Play,
+    Endless,
+    Versus,
+    Coop,
+    LevelSelect,
+    HighScores,
+    Editor,
+    Level(usize),
+    Resume,
+    NextLevel,
+    Restart,
+    Settings,
+    QuitToTitle,
+    BackToEditor,
+    Quit,
+    Volume,
+    Difficulty,
+    Theme,
+    ReducedMotion,
+    BrickPatterns,
+    Crt,
+    ChromaticAberration,
+    Vignette,
+    Glow,
+    Bindings,
+    Rebind(InputAction),
+    Back,
+}
+
+impl MenuItem {
+    fn label(self, settings: &Settings, rebinding: Option<InputAction>) -> String {
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        match self {
+            MenuItem::Play => "Play".to_string(),
+            MenuItem::Endless => "Endless".to_string(),
+            MenuItem::Versus => "Versus".to_string(),
+            MenuItem::Coop => "Co-op".to_string(),
+            MenuItem::LevelSelect => "Level select".to_string(),
+            MenuItem::HighScores => "High scores".to_string(),
+            MenuItem::Editor => "Level editor".to_string(),
+            MenuItem::Level(level) => format!("Level {}", level),
+            MenuItem::Resume => "Resume".to_string(),
+            MenuItem::NextLevel => "Next level".to_string(),
+            MenuItem::Restart => "Restart".to_string(),
+            MenuItem::Settings => "Settings".to_string(),
+            MenuItem::QuitToTitle => "Quit to title".to_string(),
+            MenuItem::BackToEditor => "Back to editor".to_string(),
+            MenuItem::Quit => "Quit".to_string(),
+            MenuItem::Volume => format!("Volume: {:.0}%", settings.volume * 100.0),
+            MenuItem::Difficulty => format!("Difficulty: {}", settings.difficulty.name()),
+            MenuItem::Theme => format!("Theme: {}", settings.theme),
+            MenuItem::ReducedMotion => {
+                format!("Reduced motion: {}", on_off(settings.reduced_motion))
+            }
+            MenuItem::BrickPatterns => {
+                format!("Brick patterns: {}", on_off(settings.brick_patterns))
+            }
+            MenuItem::Crt => format!("CRT: {}", on_off(settings.post_process.crt)),
+            MenuItem::ChromaticAberration => format!(
+                "Chromatic aberration: {}",
+                on_off(settings.post_process.chromatic_aberration)
+            ),
+            MenuItem::Vignette => format!("Vignette: {}", on_off(settings.post_process.vignette)),
+            MenuItem::Glow => format!("Glow: {}", on_off(settings.post_process.glow)),
+            MenuItem::Bindings => "Bindings".to_string(),
+            MenuItem::Rebind(action) if rebinding == Some(action) => {
+                format!("{}: press a button, Esc to cancel", action.name())
+            }
+            MenuItem::Rebind(action) => {
+                let bindings: Vec<String> = settings
+                    .bindings
+                    .bindings(action)
+                    .iter()
+                    .map(|b| b.to_string())
+                    .collect();
+                format!("{}: {}", action.name(), bindings.join(", "))
+            }
+            MenuItem::Back => "Back".to_string(),
+        }
+    }
+}
+
+/// The menu shown on the title screen and over the paused game
+#[derive(Debug, Default, Resource)]
+pub struct Menu {
+    pub screen: Option<MenuScreen>,
+    pub selected: usize,
+    /// the action waiting for the next button pressed to be bound to it
+    pub rebinding: Option<InputAction>,
+    /// the title or the pause screen, which the settings lead back to
+    pub root: MenuScreen,
+}
+
+impl Menu {
+    pub fn open(&mut self, screen: MenuScreen) {
+        if matches!(screen, MenuScreen::Title | MenuScreen::Pause) {
+            self.root = screen;
+        }
+        self.screen = Some(screen);
+        self.selected = 0;
+    }
+    fn play(&mut self, next_state: &mut NextState<AppState>) {
+        self.screen = None;
+        next_state.set(AppState::Playing);
+    }
+    /// Go back one screen, returning to the game from the pause screen.
+    fn back(&mut self, next_state: &mut NextState<AppState>) {
+        match self.screen {
+            Some(MenuScreen::Pause) => self.play(next_state),
+            Some(MenuScreen::Settings) => self.open(self.root),
+            Some(MenuScreen::Bindings) => self.open(MenuScreen::Settings),
+            Some(MenuScreen::LevelSelect | MenuScreen::HighScores) => self.open(MenuScreen::Title),
+            // the summaries wait for a choice
+            Some(MenuScreen::Title | MenuScreen::LevelCleared | MenuScreen::GameOver) | None => (),
+        }
+    }
+}
+
+/// Sent by the menus to replace the game in progress
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NewGameEvent {
+    /// the campaign, from `level` on
+    Start { level: usize },
+    /// generated walls, wave after wave
+    Endless { seed: u64 },
+    /// two players on the same keyboard
+    Versus,
+    /// the campaign for two players side by side
+    Coop,
+    /// the level after the one cleared, keeping the score and the lives
+    NextLevel,
+    /// the same game again, from the level it started on
+    Restart,
+    /// a game played by the computer behind the title screen
+    Attract,
+}
+
+#[derive(Component)]
+pub struct MenuRoot;
+
+/// The index of an item on the current screen
+#[derive(Component)]
+pub struct MenuButton(usize);
+
+/// Step through `len` options, wrapping around.
+fn cycle(index: usize, len: usize, step: isize) -> usize {
+    (index as isize + step).rem_euclid(len as isize) as usize
+}
+
+pub fn open_title_menu(mut menu: ResMut<Menu>) {
+    menu.open(MenuScreen::Title);
+}
+
+/// Open and close the menu, and navigate it with the keyboard, a gamepad or the mouse.
+#[allow(clippy::too_many_arguments)]
+pub fn menu_input_system(
+    actions: Res<ActionState>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut menu: ResMut<Menu>,
+    mut settings: ResMut<Settings>,
+    themes: Res<Assets<Theme>>,
+    scoreboard: Res<Scoreboard>,
+    mut records: ResMut<Records>,
+    editor: Res<Editor>,
+    autopilot: Res<Autopilot>,
+    bot: Res<Bot>,
+    versus: Res<Versus>,
+    coop: Res<Coop>,
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut new_game: EventWriter<NewGameEvent>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if menu.rebinding.is_some() {
+        return;
+    }
+    if state.0 == AppState::Playing {
+        if actions.just_pressed(InputAction::Pause) {
+            next_state.set(AppState::Paused);
+            menu.open(MenuScreen::Pause);
+        }
+        return;
+    }
+    let Some(screen) = menu.screen else { return; };
+    if actions.just_pressed(InputAction::Pause) || actions.just_pressed(InputAction::Back) {
+        menu.back(&mut next_state);
+        return;
+    }
+
+    let items = screen.items(&records, editor.play_testing);
+    // play-tests, the autopilot, bots, games of two and games already over don't enter the high
+    // scores
+    let unranked = editor.play_testing
+        || autopilot.playing
+        || bot.connected
+        || versus.active
+        || coop.active
+        || state.0 == AppState::GameOver;
+    let mut selected = menu.selected.min(items.len() - 1);
+    if actions.just_pressed(InputAction::MenuUp) {
+        selected = cycle(selected, items.len(), -1);
+    }
+    if actions.just_pressed(InputAction::MenuDown) {
+        selected = cycle(selected, items.len(), 1);
+    }
+    let mut confirm = actions.just_pressed(InputAction::Confirm);
+    for (interaction, button) in buttons.iter() {
+        match interaction {
+            Interaction::Clicked => {
+                selected = button.0;
+                confirm = true;
+            }
+            Interaction::Hovered => selected = button.0,
+            Interaction::None => (),
+        }
+    }
+    if menu.selected != selected {
+        menu.selected = selected;
+    }
+    // left and right adjust the values, confirming steps forward
+    let step = if confirm {
+        1
+    } else if actions.just_pressed(InputAction::MoveLeft) {
+        -1
+    } else if actions.just_pressed(InputAction::MoveRight) {
+        1
+    } else {
+        return;
+    };
+
+    match items[selected] {
+        MenuItem::Volume => {
+            let volume = ((settings.volume / VOLUME_STEP).round() + step as f32) * VOLUME_STEP;
+            // confirming past the maximum wraps around to silence
+            settings.volume = if confirm && 1.0 + VOLUME_STEP / 2.0 < volume {
+                0.0
+            } else {
+                volume.clamp(0.0, 1.0)
+            };
+        }
+        MenuItem::Difficulty => {
+            let all = Difficulty::ALL;
+            let index = all.iter().position(|&d| d == settings.difficulty);
+            settings.difficulty = all[cycle(index.unwrap_or_default(), all.len(), step)];
+        }
+        MenuItem::Theme => {
+            let mut names: Vec<&String> = themes.iter().map(|(_, theme)| &theme.name).collect();
+            names.sort();
+            names.dedup();
+            if !names.is_empty() {
+                let index = names.iter().position(|&name| *name == settings.theme);
+                settings.theme = names[cycle(index.unwrap_or_default(), names.len(), step)].clone();
+            }
+        }
+        MenuItem::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+        MenuItem::BrickPatterns => settings.brick_patterns = !settings.brick_patterns,
+        MenuItem::Crt => settings.post_process.crt = !settings.post_process.crt,
+        MenuItem::ChromaticAberration => {
+            let effects = &mut settings.post_process;
+            effects.chromatic_aberration = !effects.chromatic_aberration;
+        }
+        MenuItem::Vignette => settings.post_process.vignette = !settings.post_process.vignette,
+        MenuItem::Glow => settings.post_process.glow = !settings.post_process.glow,
+        _ if !confirm => (),
+        MenuItem::Play => {
+            new_game.send(NewGameEvent::Start { level: 1 });
+            menu.play(&mut next_state);
+        }
+        MenuItem::Endless => {
+            new_game.send(NewGameEvent::Endless { seed: random() });
+            menu.play(&mut next_state);
+        }
+        MenuItem::Versus => {
+            new_game.send(NewGameEvent::Versus);
+            menu.play(&mut next_state);
+        }
+        MenuItem::Coop => {
+            new_game.send(NewGameEvent::Coop);
+            menu.play(&mut next_state);
+        }
+        MenuItem::LevelSelect => menu.open(MenuScreen::LevelSelect),
+        MenuItem::HighScores => menu.open(MenuScreen::HighScores),
+        MenuItem::Editor => {
+            menu.screen = None;
+            next_state.set(AppState::Editor);
+        }
+        MenuItem::Level(level) => {
+            new_game.send(NewGameEvent::Start { level });
+            menu.play(&mut next_state);
+        }
+        MenuItem::Resume => menu.play(&mut next_state),
+        MenuItem::NextLevel => {
+            new_game.send(NewGameEvent::NextLevel);
+            menu.play(&mut next_state);
+        }
+        MenuItem::Restart => {
+            if !unranked {
+                records.record(scoreboard.score, scoreboard.level);
+            }
+            new_game.send(NewGameEvent::Restart);
+            menu.play(&mut next_state);
+        }
+        MenuItem::Settings => menu.open(MenuScreen::Settings),
+        MenuItem::QuitToTitle => {
+            if !unranked {
+                records.record(scoreboard.score, scoreboard.level);
+            }
+            new_game.send(NewGameEvent::Attract);
+            next_state.set(AppState::Title);
+        }
+        MenuItem::BackToEditor => {
+            menu.screen = None;
+            next_state.set(AppState::Editor);
+        }
+        MenuItem::Quit => exit.send(AppExit),
+        MenuItem::Bindings => menu.open(MenuScreen::Bindings),
+        MenuItem::Rebind(action) => menu.rebinding = Some(action),
+        MenuItem::Back => menu.back(&mut next_state),
+    }
+}
+
+/// Bind the next button pressed to the action picked in the bindings screen.
+pub fn rebind_system(
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut menu: ResMut<Menu>,
+    mut settings: ResMut<Settings>,
+) {
+    // skip the frame the rebinding starts, so the confirming button isn't taken
+    if menu.is_changed() {
+        return;
+    }
+    let Some(action) = menu.rebinding else { return; };
+    if keyboard.just_pressed(KeyCode::Escape) {
+        menu.rebinding = None;
+        return;
+    }
+    let binding = keyboard
+        .get_just_pressed()
+        .next()
+        .map(|&key| Binding::Key(key))
+        .or_else(|| mouse.get_just_pressed().next().map(|&b| Binding::Mouse(b)))
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|b| Binding::Gamepad(b.button_type))
+        });
+    if let Some(binding) = binding {
+        settings.bindings.rebind(action, binding);
+        menu.rebinding = None;
+    }
+}
+
+/// Rebuild the menu whenever it or what it shows changes.
+#[allow(clippy::too_many_arguments)]
+pub fn menu_ui_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    menu: Res<Menu>,
+    settings: Res<Settings>,
+    records: Res<Records>,
+    editor: Res<Editor>,
+    statistics: Res<Statistics>,
+    versus: Res<Versus>,
+    coop: Res<Coop>,
+    theme: Res<ActiveTheme>,
+    roots: Query<Entity, With<MenuRoot>>,
+) {
+    if !menu.is_changed() && !settings.is_changed() && !records.is_changed() && !theme.is_changed()
+    {
+        return;
+    }
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+    let Some(screen) = menu.screen else { return; };
+    let theme = &theme.0;
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    // the game played behind the title screen stays visible
+    let (shade, title_size) = if screen == MenuScreen::Title {
+        (0.25, LOGO_FONT_SIZE)
+    } else {
+        (0.6, TITLE_FONT_SIZE)
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::all(Val::Percent(100.0)),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, shade).into(),
+            z_index: ZIndex::Global(10),
+            ..Default::default()
+        })
+        .insert(MenuRoot)
+        .with_children(|root| {
+            root.spawn(
+                TextBundle::from_section(
+                    screen.title(),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: title_size,
+                        color: theme.value,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(20.0)),
+                    ..Default::default()
+                }),
+            );
+            for line in screen.lines(&records, &statistics, &versus, &coop) {
+                root.spawn(TextBundle::from_section(
+                    line,
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                        font_size: ITEM_FONT_SIZE,
+                        color: theme.label,
+                    },
+                ));
+            }
+            for (index, item) in screen
+                .items(&records, editor.play_testing)
+                .into_iter()
+                .enumerate()
+            {
+                let selected = index == menu.selected;
+                root.spawn(ButtonBundle {
+                    style: Style {
+                        padding: UiRect::new(
+                            Val::Px(20.0),
+                            Val::Px(20.0),
+                            Val::Px(4.0),
+                            Val::Px(4.0),
+                        ),
+                        margin: UiRect::all(Val::Px(4.0)),
+                        ..Default::default()
+                    },
+                    background_color: if selected {
+                        Color::rgba(1.0, 1.0, 1.0, 0.15)
+                    } else {
+                        Color::NONE
+                    }
+                    .into(),
+                    ..Default::default()
+                })
+                .insert(MenuButton(index))
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section(
+                        item.label(&settings, menu.rebinding),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: ITEM_FONT_SIZE,
+                            color: if selected { theme.accent } else { theme.label },
+                        },
+                    ));
+                });
+            }
+        });
+}
@@ -0,0 +1,101 @@
+use {
+    crate::level::BrickKind,
+    bevy::{
+        prelude::*,
+        render::{
+            render_resource::{Extent3d, TextureDimension, TextureFormat},
+            texture::ImageSampler,
+        },
+        utils::HashMap,
+    },
+};
+
+/// Size of the pattern textures, roughly the shape of a brick
+const PATTERN_WIDTH: u32 = 80;
+const PATTERN_HEIGHT: u32 = 16;
+/// Brightness of the marked pixels, which darkens the tint of the sprite
+const PATTERN_SHADE: u8 = 130;
+
+/// A texture telling bricks apart without relying on their color
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BrickPattern {
+    Plain,
+    Stripes,
+    Dots,
+    Checker,
+    Grid,
+}
+
+impl BrickPattern {
+    pub const ALL: [BrickPattern; 5] = [
+        BrickPattern::Plain,
+        BrickPattern::Stripes,
+        BrickPattern::Dots,
+        BrickPattern::Checker,
+        BrickPattern::Grid,
+    ];
+    /// The pattern of a brick of `kind` taking `hp` more hits, the tougher ones more marked
+    pub fn for_brick(kind: BrickKind, hp: usize) -> Self {
+        match (kind, hp) {
+            (BrickKind::Steel, _) => BrickPattern::Grid,
+            (BrickKind::Bonus, _) => BrickPattern::Dots,
+            (BrickKind::Normal, 0 | 1) => BrickPattern::Plain,
+            (BrickKind::Normal, 2) => BrickPattern::Stripes,
+            (BrickKind::Normal, _) => BrickPattern::Checker,
+        }
+    }
+    /// Whether the pixel at (`x`, `y`) is marked.
+    fn marks(self, x: u32, y: u32) -> bool {
+        match self {
+            BrickPattern::Plain => false,
+            BrickPattern::Stripes => ((x + y) / 4).is_multiple_of(2),
+            BrickPattern::Dots => {
+                let dx = (x % 8) as f32 - 3.5;
+                let dy = (y % 8) as f32 - 3.5;
+                dx * dx + dy * dy < 7.0
+            }
+            BrickPattern::Checker => (x / 8 + y / 8).is_multiple_of(2),
+            BrickPattern::Grid => x.is_multiple_of(8) || y.is_multiple_of(8),
+        }
+    }
+    /// A white texture with the pattern in gray, to be tinted by the sprite color.
+    pub fn image(self) -> Image {
+        let mut data = Vec::with_capacity((4 * PATTERN_WIDTH * PATTERN_HEIGHT) as usize);
+        for y in 0..PATTERN_HEIGHT {
+            for x in 0..PATTERN_WIDTH {
+                let shade = if self.marks(x, y) { PATTERN_SHADE } else { 255 };
+                data.extend_from_slice(&[shade, shade, shade, 255]);
+            }
+        }
+        let mut image = Image::new(
+            Extent3d {
+                width: PATTERN_WIDTH,
+                height: PATTERN_HEIGHT,
+                ..default()
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        image.sampler_descriptor = ImageSampler::nearest();
+        image
+    }
+}
+
+/// The textures of every [`BrickPattern`]
+#[derive(Debug, Default, Resource)]
+pub struct BrickPatterns(pub HashMap<BrickPattern, Handle<Image>>);
+
+impl BrickPatterns {
+    pub fn get(&self, pattern: BrickPattern) -> Handle<Image> {
+        self.0.get(&pattern).cloned().unwrap_or_default()
+    }
+}
+
+pub fn setup_brick_patterns(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let patterns = BrickPattern::ALL
+        .iter()
+        .map(|&pattern| (pattern, images.add(pattern.image())))
+        .collect();
+    commands.insert_resource(BrickPatterns(patterns));
+}
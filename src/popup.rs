@@ -0,0 +1,70 @@
+use {
+    crate::{
+        theme::ActiveTheme,
+        tween::{Ease, Lens, OnComplete, Tween, Tweens},
+    },
+    bevy::prelude::*,
+};
+
+const POPUP_Z: f32 = 5.0;
+const POPUP_FONT_SIZE: f32 = 36.0;
+
+/// Request a popup showing the points earned at a position in world space
+pub struct ScorePopupEvent {
+    pub position: Vec2,
+    pub points: usize,
+}
+
+pub fn score_popup_spawner_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<ActiveTheme>,
+    mut events: EventReader<ScorePopupEvent>,
+) {
+    for event in events.iter() {
+        // bigger combos get bigger, longer-lived popups
+        let emphasis = (event.points as f32).sqrt().min(3.0);
+        let color = theme.0.accent;
+        let lifetime = 0.6 + 0.2 * emphasis;
+        let start = event.position.extend(POPUP_Z);
+        commands
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    format!("+{}", event.points),
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: POPUP_FONT_SIZE,
+                        color,
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                transform: Transform {
+                    translation: start,
+                    scale: Vec3::splat(0.8 + 0.2 * emphasis),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(
+                Tweens::new(
+                    Tween::new(
+                        Lens::Translation {
+                            start,
+                            end: start + Vec3::Y * (50.0 + 20.0 * emphasis),
+                        },
+                        Ease::CubicOut,
+                        lifetime,
+                    )
+                    .on_complete(OnComplete::Despawn),
+                )
+                .with(Tween::new(
+                    Lens::TextColor {
+                        start: color,
+                        end: color.with_a(0.0),
+                    },
+                    Ease::QuadIn,
+                    lifetime,
+                )),
+            );
+    }
+}
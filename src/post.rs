@@ -0,0 +1,253 @@
+use {
+    crate::{arena::ArenaCamera, settings::Settings, tween::Motion},
+    bevy::{
+        prelude::*,
+        reflect::TypeUuid,
+        render::{
+            camera::RenderTarget,
+            mesh::MeshVertexBufferLayout,
+            render_resource::{
+                AsBindGroup, Extent3d, RenderPipelineDescriptor, ShaderRef,
+                SpecializedMeshPipelineError, TextureDescriptor, TextureDimension, TextureFormat,
+                TextureUsages,
+            },
+            texture::BevyDefault,
+            view::RenderLayers,
+        },
+        sprite::{Material2d, Material2dKey, MaterialMesh2dBundle},
+        window::{PrimaryWindow, WindowRef, WindowResized},
+    },
+    serde::{Deserialize, Serialize},
+};
+
+/// Seen only by the post-process camera, so the quad doesn't show up in the arena
+const POST_PROCESS_LAYER: u8 = (RenderLayers::TOTAL_LAYERS - 1) as u8;
+
+/// The effects applied to the whole screen. With none of them enabled, the arena camera renders
+/// straight to the window.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub struct PostProcessSettings {
+    /// scanlines and a curved screen
+    pub crt: bool,
+    /// color fringes growing towards the edges, spiking on hits
+    pub chromatic_aberration: bool,
+    pub vignette: bool,
+    /// the bright parts bleeding light around them
+    pub glow: bool,
+}
+
+impl PostProcessSettings {
+    pub fn any(self) -> bool {
+        self.crt || self.chromatic_aberration || self.vignette || self.glow
+    }
+}
+
+/// The gameplay state the post-process pass reacts to
+#[derive(Debug, Default, Resource)]
+pub struct PostProcessFx {
+    /// set to 1.0 on a hit, then fades out
+    pub aberration: f32,
+}
+
+#[derive(Component)]
+pub struct PostProcessCamera;
+
+/// The quad showing the image rendered by the [`ArenaCamera`]
+#[derive(Component)]
+pub struct PostProcessQuad;
+
+#[derive(AsBindGroup, Debug, Clone, TypeUuid)]
+#[uuid = "3d1f7c52-9a0e-4b8e-a6a1-5f0c2e7d9b14"]
+#[bind_group_data(PostProcessSettings)]
+pub struct PostProcessMaterial {
+    /// selects the effects as shader defs
+    pub effects: PostProcessSettings,
+    /// the arena as rendered by the [`ArenaCamera`]
+    #[texture(0)]
+    #[sampler(1)]
+    pub source: Handle<Image>,
+    #[uniform(2)]
+    pub time: f32,
+    #[uniform(2)]
+    pub aberration: f32,
+    /// the size of `source` in pixels
+    #[uniform(2)]
+    pub resolution: Vec2,
+    /// the decorative motion left, see [`Motion`]
+    #[uniform(2)]
+    pub motion: f32,
+}
+
+impl From<&PostProcessMaterial> for PostProcessSettings {
+    fn from(material: &PostProcessMaterial) -> Self {
+        material.effects
+    }
+}
+
+impl Material2d for PostProcessMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/post_process.wgsl".into()
+    }
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        key: Material2dKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let effects = key.bind_group_data;
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            for (enabled, def) in [
+                (effects.crt, "CRT"),
+                (effects.chromatic_aberration, "CHROMATIC_ABERRATION"),
+                (effects.vignette, "VIGNETTE"),
+                (effects.glow, "GLOW"),
+            ] {
+                if enabled {
+                    fragment.shader_defs.push(def.into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The render target size for a window. The arena is rendered at the logical resolution, which
+/// suits the retro look and keeps the cursor mapping of the [`ArenaCamera`] unchanged.
+fn target_size(window: Option<&Window>) -> Extent3d {
+    let (width, height) = window.map_or((1.0, 1.0), |w| (w.width(), w.height()));
+    Extent3d {
+        width: (width as u32).max(1),
+        height: (height as u32).max(1),
+        ..default()
+    }
+}
+
+pub fn setup_post_process(
+    mut commands: Commands,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PostProcessMaterial>>,
+) {
+    let size = target_size(windows.get_single().ok());
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::bevy_default(),
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    // fill image.data with zeroes
+    image.resize(size);
+
+    let layer = RenderLayers::layer(POST_PROCESS_LAYER);
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(Mesh::from(shape::Quad::default())).into(),
+            material: materials.add(PostProcessMaterial {
+                effects: PostProcessSettings::default(),
+                source: images.add(image),
+                time: 0.0,
+                aberration: 0.0,
+                resolution: Vec2::new(size.width as f32, size.height as f32),
+                motion: 1.0,
+            }),
+            transform: Transform::from_scale(Vec3::new(size.width as f32, size.height as f32, 1.0)),
+            ..default()
+        },
+        layer,
+        PostProcessQuad,
+    ));
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                // after the arena camera, and only once an effect is enabled
+                order: 1,
+                is_active: false,
+                ..default()
+            },
+            ..default()
+        },
+        layer,
+        PostProcessCamera,
+    ));
+}
+
+/// Route the arena camera through the post-process pass while an effect is enabled, and keep
+/// the render target as large as the window.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn post_process_system(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut resized: EventReader<WindowResized>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<PostProcessMaterial>>,
+    mut quads: Query<
+        (&Handle<PostProcessMaterial>, &mut Transform),
+        (With<PostProcessQuad>, Without<PostProcessCamera>),
+    >,
+    mut cameras: ParamSet<(
+        Query<(Entity, &mut Camera), With<ArenaCamera>>,
+        Query<&mut Camera, With<PostProcessCamera>>,
+    )>,
+) {
+    let resized = resized.iter().last().is_some();
+    if !settings.is_changed() && !resized {
+        return;
+    }
+    let effects = settings.post_process;
+    let enabled = effects.any();
+    let size = target_size(windows.get_single().ok());
+    let mut target = None;
+    for (handle, mut transform) in quads.iter_mut() {
+        let Some(material) = materials.get_mut(handle) else { continue; };
+        material.effects = effects;
+        material.resolution = Vec2::new(size.width as f32, size.height as f32);
+        if let Some(image) = images.get_mut(&material.source) {
+            if image.texture_descriptor.size != size {
+                image.resize(size);
+            }
+        }
+        transform.scale = material.resolution.extend(1.0);
+        target = Some(material.source.clone());
+    }
+    let Some(target) = target else { return; };
+    for (entity, mut camera) in cameras.p0().iter_mut() {
+        camera.target = if enabled {
+            RenderTarget::Image(target.clone())
+        } else {
+            RenderTarget::Window(WindowRef::Primary)
+        };
+        // the UI is drawn by the post-process camera instead, without the effects
+        commands
+            .entity(entity)
+            .insert(UiCameraConfig { show_ui: !enabled });
+    }
+    for mut camera in cameras.p1().iter_mut() {
+        camera.is_active = enabled;
+    }
+}
+
+/// Feed the time, the hit spikes and the reduced-motion preference to the post-process material.
+pub fn post_process_uniform_system(
+    time: Res<Time>,
+    motion: Res<Motion>,
+    mut fx: ResMut<PostProcessFx>,
+    mut materials: ResMut<Assets<PostProcessMaterial>>,
+) {
+    fx.aberration *= (-5.0 * time.delta_seconds()).exp();
+    for (_, material) in materials.iter_mut() {
+        material.time = time.elapsed_seconds_wrapped();
+        material.aberration = fx.aberration;
+        material.motion = motion.0;
+    }
+}
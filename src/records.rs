@@ -0,0 +1,68 @@
+use {
+    crate::settings::{load_ron, save_ron},
+    bevy::prelude::*,
+    serde::{Deserialize, Serialize},
+    std::io,
+};
+
+/// The records file, next to the settings file
+pub const RECORDS_FILE: &str = "records.ron";
+/// How many scores the table keeps
+pub const HIGH_SCORE_COUNT: usize = 10;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HighScore {
+    pub score: usize,
+    /// the level the game ended on
+    pub level: usize,
+}
+
+/// What the player achieved across sessions
+#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[serde(default)]
+pub struct Records {
+    /// the best scores first
+    pub high_scores: Vec<HighScore>,
+    /// the levels up to this one can be picked in the level select
+    pub highest_level: usize,
+}
+
+impl Default for Records {
+    fn default() -> Self {
+        Records {
+            high_scores: Vec::new(),
+            highest_level: 1,
+        }
+    }
+}
+
+impl Records {
+    pub fn load() -> Self {
+        load_ron(RECORDS_FILE)
+    }
+    pub fn save(&self) -> io::Result<()> {
+        save_ron(RECORDS_FILE, self)
+    }
+    /// Enter a finished game into the table, returning whether it made it.
+    pub fn record(&mut self, score: usize, level: usize) -> bool {
+        if score == 0 {
+            return false;
+        }
+        let rank = self.high_scores.partition_point(|h| score <= h.score);
+        if HIGH_SCORE_COUNT <= rank {
+            return false;
+        }
+        self.high_scores.insert(rank, HighScore { score, level });
+        self.high_scores.truncate(HIGH_SCORE_COUNT);
+        true
+    }
+}
+
+/// Write the records back whenever something modified them.
+pub fn save_records_system(records: Res<Records>) {
+    if records.is_changed() && !records.is_added() {
+        if let Err(e) = records.save() {
+            warn!("failed to save {}: {}", RECORDS_FILE, e);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use {
+    crate::{input::InputBindings, post::PostProcessSettings, theme::DEFAULT_THEME, tween::Motion},
+    bevy::prelude::*,
+    serde::{de::DeserializeOwned, Deserialize, Serialize},
+    std::{fs, io},
+};
+
+/// The settings file, looked up in the working directory just like `assets/`
+pub const SETTINGS_FILE: &str = "settings.ron";
+
+/// Read a RON file, falling back to the defaults if it is missing or broken.
+pub(crate) fn load_ron<T: Default + DeserializeOwned>(path: &str) -> T {
+    let Ok(content) = fs::read_to_string(path) else {
+        return T::default();
+    };
+    ron::from_str(&content).unwrap_or_else(|e| {
+        warn!("ignore a broken {}: {}", path, e);
+        T::default()
+    })
+}
+
+pub(crate) fn save_ron<T: Serialize>(path: &str, value: &T) -> io::Result<()> {
+    let content = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, content)
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+    /// The factor applied to the speed of the ball
+    pub fn ball_speed(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.3,
+        }
+    }
+}
+
+/// User preferences persisted across sessions
+#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub bindings: InputBindings,
+    /// master volume in `0.0..=1.0`
+    pub volume: f32,
+    pub difficulty: Difficulty,
+    pub post_process: PostProcessSettings,
+    /// the name of a built-in theme or of one in `assets/themes`
+    pub theme: String,
+    /// dampen the scaling, wobbling and flashing
+    pub reduced_motion: bool,
+    /// draw a distinct pattern on each kind of brick, on top of its color
+    pub brick_patterns: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            bindings: InputBindings::default(),
+            volume: 0.8,
+            difficulty: Difficulty::Normal,
+            post_process: PostProcessSettings::default(),
+            theme: DEFAULT_THEME.to_string(),
+            reduced_motion: false,
+            brick_patterns: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Read the settings file, falling back to the defaults if it is missing or broken.
+    pub fn load() -> Self {
+        let mut settings: Settings = load_ron(SETTINGS_FILE);
+        settings.bindings.complete();
+        settings
+    }
+    pub fn save(&self) -> io::Result<()> {
+        save_ron(SETTINGS_FILE, self)
+    }
+}
+
+/// What is left of the decorative motion in reduced-motion mode
+const REDUCED_MOTION: f32 = 0.2;
+
+/// Apply the reduced-motion preference to the tweens.
+pub fn motion_settings_system(settings: Res<Settings>, mut motion: ResMut<Motion>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let scale = if settings.reduced_motion {
+        REDUCED_MOTION
+    } else {
+        1.0
+    };
+    if motion.0 != scale {
+        motion.0 = scale;
+    }
+}
+
+/// Write the settings back whenever something modified them.
+pub fn save_settings_system(settings: Res<Settings>) {
+    if settings.is_changed() && !settings.is_added() {
+        if let Err(e) = settings.save() {
+            warn!("failed to save {}: {}", SETTINGS_FILE, e);
+        }
+    }
+}
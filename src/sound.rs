@@ -0,0 +1,198 @@
+use {
+    crate::{bot::Bot, game::GameEvent, settings::Settings},
+    bevy::{
+        audio::{AddAudioSource, Source},
+        prelude::*,
+        reflect::TypeUuid,
+        utils::Duration,
+    },
+    std::f32::consts::TAU,
+};
+
+const SAMPLE_RATE: u32 = 44_100;
+/// seconds the sound takes to rise at the start, avoiding a click
+const ATTACK: f32 = 0.005;
+/// how much higher each brick of a combo sounds than the previous one
+const COMBO_PITCH: f32 = 0.06;
+/// the highest combo raising the pitch
+const MAX_COMBO_PITCH: usize = 12;
+
+/// A short tone sliding from one pitch to another, fading out as it goes. The game ships no sound
+/// files, so its effects are synthesized.
+#[derive(Clone, Copy, Debug, TypeUuid)]
+#[uuid = "99cf7309-c3da-400f-ba08-48f893c20c78"]
+pub struct Blip {
+    /// the frequency at the start in Hz
+    pub start: f32,
+    /// the frequency at the end in Hz
+    pub end: f32,
+    /// in seconds
+    pub duration: f32,
+    /// a square wave, harsher than the default sine
+    pub square: bool,
+}
+
+impl Blip {
+    pub const fn new(start: f32, end: f32, duration: f32) -> Self {
+        Blip {
+            start,
+            end,
+            duration,
+            square: false,
+        }
+    }
+    pub const fn square(mut self) -> Self {
+        self.square = true;
+        self
+    }
+    /// The sample `t` seconds in.
+    pub fn sample(&self, t: f32) -> f32 {
+        if t < 0.0 || self.duration <= t {
+            return 0.0;
+        }
+        // the phase is the integral of the linearly sliding frequency
+        let cycles = self.start * t + (self.end - self.start) * t * t / (2.0 * self.duration);
+        let wave = (cycles.fract() * TAU).sin();
+        let wave = if self.square {
+            // a square wave at full scale is much louder than a sine
+            0.5 * wave.signum()
+        } else {
+            wave
+        };
+        let envelope = (t / ATTACK).min(1.0) * (1.0 - t / self.duration);
+        wave * envelope
+    }
+    fn samples(&self) -> u32 {
+        (self.duration * SAMPLE_RATE as f32) as u32
+    }
+}
+
+impl Decodable for Blip {
+    type DecoderItem = f32;
+    type Decoder = BlipDecoder;
+    fn decoder(&self) -> Self::Decoder {
+        BlipDecoder {
+            blip: *self,
+            index: 0,
+        }
+    }
+}
+
+/// The samples of a [`Blip`], in mono
+pub struct BlipDecoder {
+    blip: Blip,
+    index: u32,
+}
+
+impl Iterator for BlipDecoder {
+    type Item = f32;
+    fn next(&mut self) -> Option<f32> {
+        if self.blip.samples() <= self.index {
+            return None;
+        }
+        let sample = self.blip.sample(self.index as f32 / SAMPLE_RATE as f32);
+        self.index += 1;
+        Some(sample)
+    }
+}
+
+impl Source for BlipDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.blip.samples().saturating_sub(self.index) as usize)
+    }
+    fn channels(&self) -> u16 {
+        1
+    }
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(self.blip.duration))
+    }
+}
+
+/// The sound effects of the [`GameEvent`]s
+#[derive(Resource)]
+pub struct Sounds {
+    brick: Handle<Blip>,
+    paddle: Handle<Blip>,
+    near_miss: Handle<Blip>,
+    penalty: Handle<Blip>,
+    level_cleared: Handle<Blip>,
+    game_over: Handle<Blip>,
+}
+
+/// Play a sound on every [`GameEvent`] at the volume of the [`Settings`]
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<Blip>()
+            .add_startup_system(setup_sounds)
+            // a bot plays headless, and much faster than anyone could listen to
+            .add_system(sound_system.run_if(|bot: Res<Bot>| !bot.connected));
+    }
+}
+
+fn setup_sounds(mut commands: Commands, mut blips: ResMut<Assets<Blip>>) {
+    commands.insert_resource(Sounds {
+        brick: blips.add(Blip::new(660.0, 880.0, 0.08)),
+        paddle: blips.add(Blip::new(330.0, 260.0, 0.07)),
+        near_miss: blips.add(Blip::new(520.0, 1040.0, 0.15)),
+        penalty: blips.add(Blip::new(220.0, 80.0, 0.4).square()),
+        level_cleared: blips.add(Blip::new(440.0, 1320.0, 0.6)),
+        game_over: blips.add(Blip::new(300.0, 60.0, 0.9).square()),
+    });
+}
+
+fn sound_system(
+    mut events: EventReader<GameEvent>,
+    settings: Res<Settings>,
+    sounds: Res<Sounds>,
+    audio: Res<Audio<Blip>>,
+) {
+    for event in events.iter() {
+        if settings.volume <= 0.0 {
+            continue;
+        }
+        let (sound, speed) = match *event {
+            // the bricks of a combo ring higher and higher
+            GameEvent::BrickHit { combo } => (
+                &sounds.brick,
+                1.0 + COMBO_PITCH * combo.min(MAX_COMBO_PITCH) as f32,
+            ),
+            GameEvent::PaddleHit { .. } => (&sounds.paddle, 1.0),
+            GameEvent::NearMiss => (&sounds.near_miss, 1.0),
+            GameEvent::Penalty => (&sounds.penalty, 1.0),
+            GameEvent::LevelCleared => (&sounds.level_cleared, 1.0),
+            GameEvent::GameOver => (&sounds.game_over, 1.0),
+        };
+        let playback = PlaybackSettings::ONCE
+            .with_volume(settings.volume)
+            .with_speed(speed);
+        audio.play_with_settings(sound.clone(), playback);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blip_fades_in_and_out_within_its_duration() {
+        for blip in [
+            Blip::new(440.0, 880.0, 0.1),
+            Blip::new(200.0, 50.0, 0.3).square(),
+        ] {
+            let samples: Vec<f32> = blip.decoder().collect();
+            let seconds = samples.len() as f32 / SAMPLE_RATE as f32;
+            assert!((seconds - blip.duration).abs() < 0.001);
+            assert_eq!(samples[0], 0.0);
+            assert!(samples.iter().all(|s| s.abs() <= 1.0));
+            assert!(samples.iter().any(|s| 0.3 < s.abs()));
+            // no click at the end
+            assert!(samples.last().unwrap().abs() < 0.01);
+            assert_eq!(blip.sample(blip.duration), 0.0);
+        }
+    }
+}
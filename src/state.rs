@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+/// The screen the game is on
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, States)]
+pub enum AppState {
+    /// the main menu, over a game played by the computer
+    #[default]
+    Title,
+    Playing,
+    Paused,
+    /// the summary of the level, before the next one
+    LevelCleared,
+    /// the summary of the run, once out of lives
+    GameOver,
+    /// the level editor, over the paddle standing still
+    Editor,
+}
+
+impl AppState {
+    /// Whether the ball and the paddle move.
+    pub fn simulates(self) -> bool {
+        matches!(self, AppState::Title | AppState::Playing)
+    }
+}
+
+/// The systems moving the ball and the paddle, while playing and behind the title screen
+#[derive(SystemSet, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Simulation;
+
+/// Run condition of the simulation
+pub fn simulating(state: Res<State<AppState>>) -> bool {
+    state.0.simulates()
+}
+
+/// Stop the clock while paused, so that tweens and shaders freeze along with the game.
+pub fn pause_time_system(state: Res<State<AppState>>, mut time: ResMut<Time>) {
+    if !state.is_changed() {
+        return;
+    }
+    if state.0 == AppState::Paused {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+}
@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+
+/// What the player did over a level or a whole run
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// the points gained, net of the penalties
+    pub score: usize,
+    pub bricks: usize,
+    pub longest_combo: usize,
+    pub penalties: usize,
+    /// seconds spent playing, pauses excluded
+    pub time: f32,
+    /// times the paddle sent the ball back up
+    pub shots: usize,
+    /// shots breaking at least one brick before coming back
+    pub hits: usize,
+}
+
+impl Stats {
+    /// The share of the shots breaking a brick, if any was taken.
+    pub fn accuracy(&self) -> Option<f32> {
+        (0 < self.shots).then(|| self.hits as f32 / self.shots as f32)
+    }
+    pub fn add(&mut self, other: &Stats) {
+        self.score += other.score;
+        self.bricks += other.bricks;
+        self.longest_combo = self.longest_combo.max(other.longest_combo);
+        self.penalties += other.penalties;
+        self.time += other.time;
+        self.shots += other.shots;
+        self.hits += other.hits;
+    }
+    /// The rows of the summary screens.
+    pub fn lines(&self) -> Vec<String> {
+        let minutes = self.time as usize / 60;
+        let seconds = self.time as usize % 60;
+        let accuracy = self
+            .accuracy()
+            .map_or("-".to_string(), |a| format!("{:.0}%", 100.0 * a));
+        [
+            ("Score", self.score.to_string()),
+            ("Bricks", self.bricks.to_string()),
+            ("Longest combo", self.longest_combo.to_string()),
+            ("Penalties", self.penalties.to_string()),
+            ("Time", format!("{}:{:02}", minutes, seconds)),
+            ("Accuracy", accuracy),
+        ]
+        .iter()
+        .map(|(name, value)| format!("{:<14}{:>8}", name, value))
+        .collect()
+    }
+}
+
+/// The statistics of the level in progress and of the run so far, tallied by the collisions
+#[derive(Debug, Default, Resource)]
+pub struct Statistics {
+    pub level: Stats,
+    /// the finished levels only
+    pub run: Stats,
+    /// the score when the level began
+    start_score: usize,
+    /// whether the ball broke a brick since the paddle last sent it up
+    scored_shot: bool,
+}
+
+impl Statistics {
+    pub fn shot(&mut self) {
+        self.level.shots += 1;
+        self.scored_shot = false;
+    }
+    pub fn brick_hit(&mut self, combo: usize) {
+        self.level.bricks += 1;
+        self.level.longest_combo = self.level.longest_combo.max(combo);
+        if !self.scored_shot && 0 < self.level.shots {
+            self.level.hits += 1;
+            self.scored_shot = true;
+        }
+    }
+    pub fn penalty(&mut self) {
+        self.level.penalties += 1;
+    }
+    /// Close the level ending with `score`, adding it to the run.
+    pub fn finish_level(&mut self, score: usize) {
+        self.level.score = score.saturating_sub(self.start_score);
+        self.run.add(&self.level);
+        self.run.score = score;
+    }
+    /// Start tallying the next level from `score`.
+    pub fn next_level(&mut self, score: usize) {
+        self.level = Stats::default();
+        self.start_score = score;
+        self.scored_shot = false;
+    }
+}
+
+/// Count the time spent playing the level.
+pub fn statistics_time_system(time: Res<Time>, mut statistics: ResMut<Statistics>) {
+    statistics.level.time += time.delta_seconds();
+}
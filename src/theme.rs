@@ -0,0 +1,203 @@
+use {
+    crate::{settings::Settings, tween::lerp_color, versus::Player},
+    bevy::{
+        asset::{AssetLoader, Error, LoadContext, LoadedAsset},
+        prelude::*,
+        reflect::TypeUuid,
+        utils::BoxedFuture,
+    },
+    serde::{Deserialize, Serialize},
+};
+
+/// The theme used when the one named in the settings doesn't exist
+pub const DEFAULT_THEME: &str = "classic";
+/// Themes of the player, loaded on top of the built-in ones
+pub const THEME_FOLDER: &str = "themes";
+
+/// A named palette for everything on screen, loaded from `*.theme.ron` files
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, TypeUuid)]
+#[uuid = "8f3e5a61-2c7d-4e0b-9b1f-6d4a7c2e8f90"]
+pub struct Theme {
+    pub name: String,
+    /// the clear color, blended over the animated background by its alpha
+    pub background: Color,
+    pub walls: Color,
+    pub paddle: Color,
+    pub mouth: Color,
+    pub ball: Color,
+    /// the bottom and the top brick rows, the rows in between are interpolated
+    pub bricks: [Color; 2],
+    /// the names of the HUD widgets
+    pub label: Color,
+    /// the values of the HUD widgets
+    pub value: Color,
+    /// the combo counter and the score popups
+    pub accent: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            name: DEFAULT_THEME.to_string(),
+            background: Color::rgba(0.9, 0.9, 0.2, 0.0),
+            walls: Color::rgb(0.8, 0.8, 0.8),
+            paddle: Color::rgb(0.5, 0.5, 1.0),
+            mouth: Color::rgb(0.1, 0.1, 0.3),
+            ball: Color::rgb(0.2, 0.3, 1.0),
+            bricks: [Color::rgb(0.5, 0.5, 1.0), Color::rgb(0.35, 0.35, 0.9)],
+            label: Color::rgb(0.5, 0.5, 1.0),
+            value: Color::rgb(1.0, 0.5, 0.5),
+            accent: Color::rgb(1.0, 0.2, 0.0),
+        }
+    }
+}
+
+impl Theme {
+    /// The themes shipped with the game, starting with the default one.
+    pub fn builtins() -> Vec<Theme> {
+        vec![
+            Theme::default(),
+            Theme {
+                name: "night".to_string(),
+                background: Color::rgba(0.03, 0.03, 0.1, 0.6),
+                walls: Color::rgb(0.25, 0.25, 0.35),
+                paddle: Color::rgb(0.3, 0.8, 0.9),
+                mouth: Color::rgb(0.05, 0.05, 0.1),
+                ball: Color::rgb(1.0, 0.9, 0.4),
+                bricks: [Color::rgb(0.2, 0.4, 0.9), Color::rgb(0.8, 0.3, 0.9)],
+                label: Color::rgb(0.5, 0.7, 1.0),
+                value: Color::rgb(1.0, 0.9, 0.5),
+                accent: Color::rgb(1.0, 0.4, 0.7),
+            },
+            // the Okabe-Ito palette, safe for protanopia and deuteranopia
+            Theme {
+                name: "okabe-ito".to_string(),
+                background: Color::rgba(0.1, 0.1, 0.1, 0.5),
+                walls: Color::rgb(0.6, 0.6, 0.6),
+                paddle: Color::rgb(0.34, 0.71, 0.91),
+                mouth: Color::rgb(0.05, 0.05, 0.05),
+                ball: Color::rgb(0.94, 0.89, 0.26),
+                bricks: [Color::rgb(0.0, 0.45, 0.7), Color::rgb(0.9, 0.62, 0.0)],
+                label: Color::rgb(0.34, 0.71, 0.91),
+                value: Color::rgb(0.94, 0.89, 0.26),
+                accent: Color::rgb(0.84, 0.37, 0.0),
+            },
+            // avoids telling blue from yellow, for tritanopia
+            Theme {
+                name: "tritan".to_string(),
+                background: Color::rgba(0.08, 0.08, 0.08, 0.5),
+                walls: Color::rgb(0.7, 0.7, 0.7),
+                paddle: Color::rgb(0.9, 0.2, 0.3),
+                mouth: Color::rgb(0.05, 0.05, 0.05),
+                ball: Color::WHITE,
+                bricks: [Color::rgb(0.85, 0.1, 0.2), Color::rgb(0.0, 0.65, 0.65)],
+                label: Color::rgb(0.0, 0.75, 0.75),
+                value: Color::WHITE,
+                accent: Color::rgb(1.0, 0.3, 0.4),
+            },
+            Theme {
+                name: "high-contrast".to_string(),
+                // hides the animated background completely
+                background: Color::BLACK,
+                walls: Color::WHITE,
+                paddle: Color::rgb(1.0, 1.0, 0.0),
+                mouth: Color::BLACK,
+                ball: Color::WHITE,
+                bricks: [Color::rgb(0.0, 1.0, 1.0), Color::rgb(1.0, 0.0, 1.0)],
+                label: Color::WHITE,
+                value: Color::rgb(1.0, 1.0, 0.0),
+                accent: Color::rgb(1.0, 0.5, 0.0),
+            },
+        ]
+    }
+    /// The color of the paddle of `player`, the second one in the accent color.
+    pub fn player_paddle(&self, player: Player) -> Color {
+        match player {
+            Player::One => self.paddle,
+            Player::Two => self.accent,
+        }
+    }
+    /// The tint of the whites of the eyes of `player`, the second one in a pale accent color
+    /// telling the faces apart.
+    pub fn player_eyes(&self, player: Player) -> Color {
+        match player {
+            Player::One => Color::WHITE,
+            Player::Two => lerp_color(Color::WHITE, self.accent, 0.5),
+        }
+    }
+    /// The color of a brick in `row` out of `rows`, counting from the bottom.
+    pub fn brick(&self, row: usize, rows: usize) -> Color {
+        let t = if rows < 2 {
+            0.0
+        } else {
+            row as f32 / (rows - 1) as f32
+        };
+        lerp_color(self.bricks[0], self.bricks[1], t)
+    }
+}
+
+#[derive(Default)]
+pub struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let theme: Theme = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(theme));
+            Ok(())
+        })
+    }
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+/// The handles of every theme, keeping them loaded
+#[derive(Debug, Default, Resource)]
+pub struct Themes(pub Vec<HandleUntyped>);
+
+/// The theme currently applied, resolved from [`Settings::theme`]
+#[derive(Debug, Default, Resource)]
+pub struct ActiveTheme(pub Theme);
+
+pub fn setup_themes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut themes: ResMut<Assets<Theme>>,
+) {
+    let mut handles: Vec<HandleUntyped> = Theme::builtins()
+        .into_iter()
+        .map(|theme| themes.add(theme).clone_untyped())
+        .collect();
+    match asset_server.load_folder(THEME_FOLDER) {
+        Ok(loaded) => handles.extend(loaded),
+        Err(e) => warn!("failed to load the themes in {}: {}", THEME_FOLDER, e),
+    }
+    commands.insert_resource(Themes(handles));
+}
+
+/// Switch to the theme named in the settings, or reload it when its file changes.
+pub fn theme_selection_system(
+    settings: Res<Settings>,
+    themes: Res<Assets<Theme>>,
+    mut events: EventReader<AssetEvent<Theme>>,
+    mut active: ResMut<ActiveTheme>,
+) {
+    let loaded = events.iter().last().is_some();
+    if !settings.is_changed() && !loaded {
+        return;
+    }
+    let theme = themes
+        .iter()
+        .map(|(_, theme)| theme)
+        .find(|theme| theme.name == settings.theme)
+        .cloned()
+        .unwrap_or_default();
+    if active.0 != theme {
+        active.0 = theme;
+    }
+}
@@ -0,0 +1,421 @@
+use {crate::game::GameRng, bevy::prelude::*, rand::Rng, std::f32::consts::PI};
+
+/// Standard easing curves, mapping the linear progress in `0.0..=1.0` to an eased one
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Ease {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    BackOut,
+    ElasticOut,
+    BounceOut,
+}
+
+impl Ease {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Ease::Linear => t,
+            Ease::QuadIn => t * t,
+            Ease::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Ease::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Ease::QuadInOut => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            Ease::CubicIn => t.powi(3),
+            Ease::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Ease::CubicInOut if t < 0.5 => 4.0 * t.powi(3),
+            Ease::CubicInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            Ease::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Ease::SineOut => (t * PI / 2.0).sin(),
+            Ease::SineInOut => -((t * PI).cos() - 1.0) / 2.0,
+            Ease::ExpoIn if t == 0.0 => 0.0,
+            Ease::ExpoIn => 2.0f32.powf(10.0 * t - 10.0),
+            Ease::ExpoOut if t == 1.0 => 1.0,
+            Ease::ExpoOut => 1.0 - 2.0f32.powf(-10.0 * t),
+            Ease::BackOut => {
+                const C1: f32 = 1.70158;
+                const C3: f32 = C1 + 1.0;
+                1.0 + C3 * (t - 1.0).powi(3) + C1 * (t - 1.0).powi(2)
+            }
+            Ease::ElasticOut if t == 0.0 || t == 1.0 => t,
+            Ease::ElasticOut => {
+                const C4: f32 = 2.0 * PI / 3.0;
+                2.0f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * C4).sin() + 1.0
+            }
+            Ease::BounceOut => {
+                const N1: f32 = 7.5625;
+                const D1: f32 = 2.75;
+                if t < 1.0 / D1 {
+                    N1 * t * t
+                } else if t < 2.0 / D1 {
+                    let t = t - 1.5 / D1;
+                    N1 * t * t + 0.75
+                } else if t < 2.5 / D1 {
+                    let t = t - 2.25 / D1;
+                    N1 * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D1;
+                    N1 * t * t + 0.984375
+                }
+            }
+        }
+    }
+}
+
+/// The property a [`Tween`] animates
+#[derive(Clone, Debug)]
+pub enum Lens {
+    Translation {
+        start: Vec3,
+        end: Vec3,
+    },
+    Scale {
+        start: Vec3,
+        end: Vec3,
+    },
+    /// rotation around the z axis in radians
+    Rotation {
+        start: f32,
+        end: f32,
+    },
+    /// random rotation around the z axis, calming down as the tween proceeds
+    Wobble {
+        amplitude: f32,
+    },
+    SpriteColor {
+        start: Color,
+        end: Color,
+    },
+    /// the color of every text section
+    TextColor {
+        start: Color,
+        end: Color,
+    },
+    /// the font size of every text section
+    TextSize {
+        start: f32,
+        end: f32,
+    },
+}
+
+pub(crate) fn lerp_color(start: Color, end: Color, t: f32) -> Color {
+    let [r0, g0, b0, a0] = start.as_rgba_f32();
+    let [r1, g1, b1, a1] = end.as_rgba_f32();
+    Color::rgba(
+        r0 + (r1 - r0) * t,
+        g0 + (g1 - g0) * t,
+        b0 + (b1 - b0) * t,
+        a0 + (a1 - a0) * t,
+    )
+}
+
+impl Lens {
+    /// Apply the eased progress `t`, with the decorative part of the motion scaled by `motion`
+    /// and drawn from `rng`.
+    fn apply(
+        &self,
+        t: f32,
+        motion: f32,
+        rng: &mut impl Rng,
+        transform: Option<&mut Transform>,
+        sprite: Option<&mut Sprite>,
+        text: Option<&mut Text>,
+    ) {
+        match *self {
+            Lens::Translation { start, end } => {
+                if let Some(transform) = transform {
+                    transform.translation = start.lerp(end, t);
+                }
+            }
+            // with less motion, scale and rotation start closer to where they end
+            Lens::Scale { start, end } => {
+                if let Some(transform) = transform {
+                    let start = end.lerp(start, motion);
+                    transform.scale = start.lerp(end, t);
+                }
+            }
+            Lens::Rotation { start, end } => {
+                if let Some(transform) = transform {
+                    let start = end + (start - end) * motion;
+                    transform.rotation = Quat::from_rotation_z(start + (end - start) * t);
+                }
+            }
+            Lens::Wobble { amplitude } => {
+                if let Some(transform) = transform {
+                    let angle = amplitude * motion * (1.0 - t) * rng.gen::<f32>();
+                    transform.rotation = Quat::from_rotation_z(angle);
+                }
+            }
+            Lens::SpriteColor { start, end } => {
+                if let Some(sprite) = sprite {
+                    sprite.color = lerp_color(start, end, t);
+                }
+            }
+            Lens::TextColor { start, end } => {
+                if let Some(text) = text {
+                    let color = lerp_color(start, end, t);
+                    for section in text.sections.iter_mut() {
+                        section.style.color = color;
+                    }
+                }
+            }
+            Lens::TextSize { start, end } => {
+                if let Some(text) = text {
+                    let size = start + (end - start) * t;
+                    for section in text.sections.iter_mut() {
+                        section.style.font_size = size;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// What happens to the entity when a [`Tween`] finishes
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OnComplete {
+    /// keep the final state and drop the tween
+    #[default]
+    Stop,
+    /// start over from the beginning
+    Repeat,
+    Despawn,
+}
+
+/// An animation of a single property along an easing curve
+#[derive(Clone, Debug)]
+pub struct Tween {
+    pub lens: Lens,
+    pub ease: Ease,
+    /// in seconds
+    pub duration: f32,
+    /// negative while delayed
+    pub elapsed: f32,
+    pub on_complete: OnComplete,
+    /// passed to [`TweenCompleted`] to tell tweens apart
+    pub tag: u32,
+}
+
+impl Tween {
+    pub fn new(lens: Lens, ease: Ease, duration: f32) -> Self {
+        Tween {
+            lens,
+            ease,
+            duration,
+            elapsed: 0.0,
+            on_complete: OnComplete::Stop,
+            tag: 0,
+        }
+    }
+    pub fn with_tag(mut self, tag: u32) -> Self {
+        self.tag = tag;
+        self
+    }
+    /// Hold the start for `delay` seconds.
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.elapsed = -delay;
+        self
+    }
+    pub fn on_complete(mut self, on_complete: OnComplete) -> Self {
+        self.on_complete = on_complete;
+        self
+    }
+    /// The linear progress in `0.0..=1.0`.
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        (self.elapsed / self.duration).clamp(0.0, 1.0)
+    }
+    pub fn is_finished(&self) -> bool {
+        self.duration <= self.elapsed
+    }
+}
+
+/// The tweens of an entity. Inserting a new one replaces all running tweens.
+#[derive(Clone, Component, Debug, Default)]
+pub struct Tweens(pub Vec<Tween>);
+
+impl Tweens {
+    pub fn new(tween: Tween) -> Self {
+        Tweens(vec![tween])
+    }
+    pub fn with(mut self, tween: Tween) -> Self {
+        self.0.push(tween);
+        self
+    }
+    pub fn is_running(&self) -> bool {
+        self.0.iter().any(|t| !t.is_finished())
+    }
+}
+
+/// How much of the decorative motion is played, from 0.0 for none to 1.0 for all of it
+///
+/// Scale, rotation and wobble are damped by it, while translations are kept as they carry meaning.
+#[derive(Clone, Copy, Debug, Resource)]
+pub struct Motion(pub f32);
+
+impl Default for Motion {
+    fn default() -> Self {
+        Motion(1.0)
+    }
+}
+
+/// Sent when a tween, except a repeating one, reaches its end
+#[derive(Clone, Copy, Debug)]
+pub struct TweenCompleted {
+    pub entity: Entity,
+    pub tag: u32,
+}
+
+#[allow(clippy::type_complexity)]
+pub fn tween_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    motion: Res<Motion>,
+    mut rng: ResMut<GameRng>,
+    mut query: Query<(
+        Entity,
+        &mut Tweens,
+        Option<&mut Transform>,
+        Option<&mut Sprite>,
+        Option<&mut Text>,
+    )>,
+    mut completed: EventWriter<TweenCompleted>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut tweens, mut transform, mut sprite, mut text) in query.iter_mut() {
+        let mut despawn = false;
+        tweens.0.retain_mut(|tween| {
+            tween.elapsed += dt;
+            let t = tween.ease.apply(tween.progress());
+            tween.lens.apply(
+                t,
+                motion.0,
+                &mut rng.0,
+                transform.as_deref_mut(),
+                sprite.as_deref_mut(),
+                text.as_deref_mut(),
+            );
+            if !tween.is_finished() {
+                return true;
+            }
+            match tween.on_complete {
+                OnComplete::Repeat => {
+                    tween.elapsed = 0.0;
+                    return true;
+                }
+                OnComplete::Despawn => despawn = true,
+                OnComplete::Stop => (),
+            }
+            completed.send(TweenCompleted {
+                entity,
+                tag: tween.tag,
+            });
+            false
+        });
+        // finished tweens leave an empty component, as removing it could drop a fresh one
+        if despawn {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::game::{lockstep_time, lockstep_time_system},
+        bevy::{ecs::event::ManualEventReader, time::TimeSystem},
+    };
+
+    const EASES: [Ease; 15] = [
+        Ease::Linear,
+        Ease::QuadIn,
+        Ease::QuadOut,
+        Ease::QuadInOut,
+        Ease::CubicIn,
+        Ease::CubicOut,
+        Ease::CubicInOut,
+        Ease::SineIn,
+        Ease::SineOut,
+        Ease::SineInOut,
+        Ease::ExpoIn,
+        Ease::ExpoOut,
+        Ease::BackOut,
+        Ease::ElasticOut,
+        Ease::BounceOut,
+    ];
+
+    #[test]
+    fn eases_start_at_zero_and_end_at_one() {
+        for ease in EASES {
+            assert!(ease.apply(0.0).abs() < 1e-5, "{:?}", ease);
+            assert!((ease.apply(1.0) - 1.0).abs() < 1e-5, "{:?}", ease);
+            // progress out of range is clamped
+            assert_eq!(ease.apply(-1.0), ease.apply(0.0), "{:?}", ease);
+            assert_eq!(ease.apply(2.0), ease.apply(1.0), "{:?}", ease);
+        }
+    }
+
+    #[test]
+    fn eases_without_overshoot_never_go_back() {
+        let overshooting = [Ease::BackOut, Ease::ElasticOut, Ease::BounceOut];
+        for ease in EASES.into_iter().filter(|e| !overshooting.contains(e)) {
+            let mut last = ease.apply(0.0);
+            for i in 1..=1000 {
+                let value = ease.apply(i as f32 / 1000.0);
+                assert!(last <= value, "{:?} goes back at {}", ease, i);
+                assert!(value <= 1.0 + 1e-5, "{:?} overshoots at {}", ease, i);
+                last = value;
+            }
+        }
+    }
+
+    #[test]
+    fn finished_tween_completes_once() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(lockstep_time())
+            .add_system(
+                lockstep_time_system
+                    .in_base_set(CoreSet::First)
+                    .before(TimeSystem),
+            )
+            .init_resource::<Motion>()
+            .init_resource::<GameRng>()
+            .add_event::<TweenCompleted>()
+            .add_system(tween_system);
+        let lens = Lens::Translation {
+            start: Vec3::ZERO,
+            end: Vec3::X,
+        };
+        let entity = app
+            .world
+            .spawn((
+                Transform::default(),
+                Tweens::new(Tween::new(lens, Ease::QuadOut, 0.35).with_tag(7)),
+            ))
+            .id();
+        let mut reader = ManualEventReader::<TweenCompleted>::default();
+        let mut completed = Vec::new();
+        for _ in 0..60 {
+            app.update();
+            let events = app.world.resource::<Events<TweenCompleted>>();
+            completed.extend(reader.iter(events).map(|e| (e.entity, e.tag)));
+        }
+        assert_eq!(completed, [(entity, 7)]);
+        let transform = app.world.get::<Transform>(entity).unwrap();
+        assert_eq!(transform.translation, Vec3::X);
+        assert!(app.world.get::<Tweens>(entity).unwrap().0.is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+use {
+    crate::{
+        arena::Arena,
+        layout::{Layout, Shape},
+        level::Level,
+    },
+    bevy::prelude::*,
+};
+
+/// One of the two players at the same keyboard, in versus or in co-op
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Player {
+    #[default]
+    One,
+    Two,
+}
+
+impl Player {
+    pub fn index(self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1,
+        }
+    }
+    pub fn other(self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Player::One => "Player 1",
+            Player::Two => "Player 2",
+        }
+    }
+    /// The player owning the bricks at height `y`, on the half of the arena they defend.
+    pub fn side(y: f32) -> Player {
+        if y < 0.0 {
+            Player::One
+        } else {
+            Player::Two
+        }
+    }
+}
+
+/// Two players on the same keyboard, the second one with a paddle in place of the top wall.
+///
+/// Each owns the bricks on their half and scores for the bricks broken by the ball they
+/// touched last. Running out of lives or of bricks loses the game.
+#[derive(Debug, Default, Resource)]
+pub struct Versus {
+    pub active: bool,
+    pub scores: [usize; 2],
+    pub lives: [usize; 2],
+    /// the bricks left to break on the side of each player
+    pub bricks: [usize; 2],
+    pub winner: Option<Player>,
+}
+
+impl Versus {
+    /// A game on `level`, with `lives` each.
+    pub fn start(level: &Level, arena: &Arena, lives: usize) -> Self {
+        let mut bricks = [0; 2];
+        let cell_size = arena.brick_size(level.rows, level.columns, level.spacing);
+        for brick in level.bricks.iter().filter(|b| b.kind.is_breakable()) {
+            let position = arena.brick_position(brick.row, brick.column, cell_size, level.spacing);
+            bricks[Player::side(position.y).index()] += 1;
+        }
+        let middle = arena.brick_area().center();
+        for (brick, offset) in level.layouts.iter().flat_map(Layout::bricks) {
+            if brick.kind.is_breakable() {
+                bricks[Player::side(middle.y + offset.y).index()] += 1;
+            }
+        }
+        Versus {
+            active: true,
+            scores: [0; 2],
+            lives: [lives; 2],
+            bricks,
+            winner: None,
+        }
+    }
+    /// The ball got past the paddle of `player`.
+    pub fn miss(&mut self, player: Player) {
+        let lives = &mut self.lives[player.index()];
+        *lives = lives.saturating_sub(1);
+        if 0 == *lives {
+            self.winner.get_or_insert(player.other());
+        }
+    }
+    /// A brick on the side of `owner` was broken.
+    pub fn brick_broken(&mut self, owner: Player) {
+        let bricks = &mut self.bricks[owner.index()];
+        *bricks = bricks.saturating_sub(1);
+        if 0 == *bricks {
+            self.winner.get_or_insert(owner.other());
+        }
+    }
+    /// The rows of the summary screen.
+    pub fn lines(&self) -> Vec<String> {
+        let winner = match self.winner {
+            Some(player) => format!("{} wins", player.name()),
+            None => "Draw".to_string(),
+        };
+        let mut lines = vec![winner];
+        lines.extend([Player::One, Player::Two].iter().map(|&player| {
+            format!(
+                "{}  {:>6}  {} lives  {} bricks",
+                player.name(),
+                self.scores[player.index()],
+                self.lives[player.index()],
+                self.bricks[player.index()],
+            )
+        }));
+        lines
+    }
+}
+
+/// Rows of bricks across the middle of the arena, half of them on each side.
+pub fn versus_level(arena: &Arena) -> Level {
+    Level {
+        name: "Versus".to_string(),
+        rows: 1,
+        columns: 1,
+        spacing: 0.0,
+        bricks: Vec::new(),
+        layouts: vec![Layout {
+            offset: -arena.brick_area().center(),
+            ..Layout::new(Shape::Hex {
+                rows: 6,
+                columns: 14,
+            })
+        }],
+    }
+}